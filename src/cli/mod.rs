@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::config::constants::STAINLESS_HOME_PATH;
+
+#[derive(Parser)]
+#[clap(author, version, about = "Keeps Minecraft servers updated and running")]
+pub struct Cli {
+    /// Directory holding the Stainless configuration and the servers' working directories
+    #[clap(
+        long,
+        global = true,
+        env = "STAINLESS_HOME",
+        default_value = STAINLESS_HOME_PATH
+    )]
+    pub home: PathBuf,
+
+    /// Path of the Stainless configuration file to use. Defaults to config.yaml in the home directory
+    #[clap(short, long, global = true, env = "STAINLESS_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Profile whose overlay file (e.g. config.prod.yaml for "prod") is merged over the config file
+    #[clap(short, long, global = true, env = "STAINLESS_PROFILE")]
+    pub profile: Option<String>,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Update and run the configured servers, restarting them when they exit
+    Run(RunArgs),
+    /// Check whether newer server builds are available without downloading them
+    Check(ServerSelection),
+    /// Download newer server builds without starting the servers
+    Update(ServerSelection),
+    /// Show the installed server build and the latest available build
+    Status(StatusArgs),
+    /// Write a new Stainless configuration file
+    Init(InitArgs),
+    /// Check the configuration for mistakes without running anything
+    Validate(ValidateArgs),
+    /// Print a JSON Schema describing Stainless configuration files
+    Schema(SchemaArgs),
+    /// Inspect the Stainless configuration
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print the configuration file
+    Show(ShowConfigArgs),
+    /// Upgrade the configuration file to the current config_version
+    Migrate(MigrateConfigArgs),
+}
+
+#[derive(Args, Default)]
+pub struct ServerSelection {
+    /// Name of a configured server to act on; may be repeated. Defaults to every server
+    #[clap(short, long = "server")]
+    pub servers: Vec<String>,
+}
+
+#[derive(Args, Default)]
+pub struct RunArgs {
+    #[clap(flatten)]
+    pub selection: ServerSelection,
+
+    /// File to append each server state change to, as a line of JSON
+    #[clap(long)]
+    pub events_file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct StatusArgs {
+    #[clap(flatten)]
+    pub selection: ServerSelection,
+
+    /// Only show the installed build without querying for the latest one
+    #[clap(long)]
+    pub offline: bool,
+}
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Skip the checks that query the APIs servers are downloaded from
+    #[clap(long)]
+    pub offline: bool,
+}
+
+#[derive(Args)]
+pub struct SchemaArgs {
+    /// File to write the schema to instead of printing it
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// Overwrite the configuration file if it already exists
+    #[clap(short, long)]
+    pub force: bool,
+
+    /// Write the built-in default configuration without asking any questions
+    #[clap(long)]
+    pub defaults: bool,
+}
+
+#[derive(Args)]
+pub struct ShowConfigArgs {
+    /// Print the configuration merged from every source, noting where each value came from
+    #[clap(long)]
+    pub resolved: bool,
+}
+
+#[derive(Args)]
+pub struct MigrateConfigArgs {
+    /// Write the upgraded configuration, keeping a backup of the original. Without this flag the
+    /// upgraded configuration is only printed
+    #[clap(long)]
+    pub write: bool,
+}
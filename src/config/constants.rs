@@ -1,6 +1,10 @@
-pub static SERVER_INFO_DIR_PATH: &str = "stainless/.clients";
-pub static STAINLESS_CONFIG_PATH: &str = "stainless/config.yaml";
-pub static STAINLESS_DEFAULT_CONFIG_URL: &str =
-    "https://raw.githubusercontent.com/nicholasgrose/stainless/main/examples/config.yaml";
+pub static STAINLESS_HOME_PATH: &str = "stainless";
+pub static STAINLESS_CONFIG_FILE_NAME: &str = "config.yaml";
+pub static SERVER_DIRECTORIES_DIR_NAME: &str = "servers";
+pub static SERVER_INFO_FILE_NAME: &str = ".stainless_client";
+pub static STAGED_SERVER_DIR_NAME: &str = ".stainless_staged";
+pub static INSTALL_DIR_NAME_PREFIX: &str = ".stainless_install_";
+pub static INSTALLED_FILES_FILE_NAME_PREFIX: &str = ".stainless_installed_";
+pub static STARTUP_TIMES_FILE_NAME: &str = ".stainless_startup.json";
 pub static DOWNLOAD_PROGRESS_BAR_TEMPLATE: &str =
     "[{elapsed_precise}] {bar:40.cyan/blue} {bytes:.1f}/{total_bytes:.1f} ({bytes_per_sec}) {msg}";
@@ -0,0 +1,167 @@
+use std::io::{BufRead, Write};
+
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use emoji::symbols::punctuation::RED_QUESTION_MARK;
+use emoji::symbols::warning::WARNING;
+use reqwest::Client;
+
+use crate::papermc::query;
+
+static CONFIG_TEMPLATE: &str = include_str!("config_template.yaml");
+
+pub struct InitAnswers {
+    pub server_name: String,
+    pub project: String,
+    pub version: String,
+    pub memory: String,
+}
+
+impl Default for InitAnswers {
+    fn default() -> Self {
+        InitAnswers {
+            server_name: String::from("TestServer"),
+            project: String::from("paper"),
+            version: String::from("1.18.1"),
+            memory: String::from("2G"),
+        }
+    }
+}
+
+pub fn render_config(answers: &InitAnswers) -> String {
+    CONFIG_TEMPLATE
+        .replace("{server_name}", &answers.server_name)
+        .replace("{project}", &answers.project)
+        .replace("{version}", &answers.version)
+        .replace("{memory}", &answers.memory)
+}
+
+pub async fn ask_init_questions(http_client: &Client) -> crate::Result<InitAnswers> {
+    let defaults = InitAnswers::default();
+
+    println!(
+        "{} Answer the following questions to create a configuration. Press enter to accept the default shown in brackets.",
+        INFORMATION.glyph
+    );
+
+    let known_projects = match query::papermc_projects(http_client).await {
+        Ok(projects) => Some(projects),
+        Err(e) => {
+            println!(
+                "{} Could not reach the PaperMC API, so answers will not be checked: {}",
+                WARNING.glyph, e
+            );
+            None
+        }
+    };
+
+    let validate_project = |project: &str| match &known_projects {
+        Some(projects) if !projects.iter().any(|known| known == project) => Err(format!(
+            "unknown project; available projects are: {}",
+            projects.join(", ")
+        )),
+        _ => Ok(()),
+    };
+    let project = ask_until_valid("PaperMC project", &defaults.project, validate_project)?;
+
+    let known_versions = match &known_projects {
+        Some(_) => query::papermc_project_versions(&project, http_client)
+            .await
+            .ok(),
+        None => None,
+    };
+    let default_version = match known_versions.as_ref().and_then(|versions| versions.last()) {
+        Some(latest_version) => latest_version.clone(),
+        None => defaults.version,
+    };
+
+    let validate_version = |version: &str| match &known_versions {
+        Some(versions) if !versions.iter().any(|known| known == version) => Err(format!(
+            "{} has no builds for this version; available versions are: {}",
+            project,
+            versions.join(", ")
+        )),
+        _ => Ok(()),
+    };
+    let version = ask_until_valid("Minecraft version", &default_version, validate_version)?;
+
+    let server_name = ask_until_valid("Server name", &defaults.server_name, |name| {
+        if name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            Ok(())
+        } else {
+            Err(String::from(
+                "only letters, digits, '-' and '_' may be used in server names",
+            ))
+        }
+    })?;
+
+    let memory = ask_until_valid(
+        "Memory size (e.g. 2G or 512M)",
+        &defaults.memory,
+        |memory| {
+            if is_valid_memory_size(memory) {
+                Ok(())
+            } else {
+                Err(String::from(
+                    "memory must be a number followed by K, M or G",
+                ))
+            }
+        },
+    )?;
+
+    Ok(InitAnswers {
+        server_name,
+        project,
+        version,
+        memory: memory.to_uppercase(),
+    })
+}
+
+fn ask_until_valid<V: Fn(&str) -> Result<(), String>>(
+    question: &str,
+    default: &str,
+    validate: V,
+) -> crate::Result<String> {
+    loop {
+        let answer = ask(question, default)?;
+
+        match validate(&answer) {
+            Ok(_) => {
+                println!("{} Using {}", CHECK_MARK.glyph, answer);
+                return Ok(answer);
+            }
+            Err(reason) => println!("{} Invalid answer, {}", WARNING.glyph, reason),
+        }
+    }
+}
+
+fn ask(question: &str, default: &str) -> crate::Result<String> {
+    print!("{} {} [{}]: ", RED_QUESTION_MARK.glyph, question, default);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+        return Err(anyhow::Error::msg("input closed before init finished"));
+    }
+
+    let answer = answer.trim();
+    if answer.is_empty() {
+        Ok(default.to_string())
+    } else {
+        Ok(answer.to_string())
+    }
+}
+
+fn is_valid_memory_size(memory: &str) -> bool {
+    match memory.char_indices().last() {
+        Some((unit_index, unit)) => {
+            matches!(unit.to_ascii_uppercase(), 'K' | 'M' | 'G')
+                && unit_index > 0
+                && memory[..unit_index].chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
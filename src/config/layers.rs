@@ -0,0 +1,644 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Error;
+use config::{Config, Map, Source, Value, ValueKind};
+
+use crate::config::migrate::migrate_document;
+use crate::config::schema::ConfigSchema;
+use crate::config::templates::resolve_templates;
+use crate::config::StainlessPaths;
+
+static ENVIRONMENT_PREFIX: &str = "STAINLESS__";
+static ENVIRONMENT_SEPARATOR: &str = "__";
+static INCLUDE_KEY: &str = "include";
+
+pub struct ConfigLayer {
+    pub name: String,
+    pub file: Option<PathBuf>,
+    pub migrated_from: Option<u32>,
+    source: Box<dyn Source + Send + Sync>,
+}
+
+impl ConfigLayer {
+    pub fn defines_key(&self, key_path: &[&str]) -> bool {
+        let mut table = match self.source.collect() {
+            Ok(table) => table,
+            Err(_) => return false,
+        };
+
+        for (depth, key) in key_path.iter().enumerate() {
+            let value = match table.remove(*key) {
+                Some(value) => value,
+                None => return false,
+            };
+
+            match value.kind {
+                ValueKind::Table(child_table) => table = child_table,
+                _ => return depth == key_path.len() - 1,
+            }
+        }
+
+        true
+    }
+}
+
+pub fn config_layers(paths: &StainlessPaths) -> crate::Result<Vec<ConfigLayer>> {
+    let mut layers = vec![];
+    let mut included_files = vec![];
+
+    push_file_layer(
+        &mut layers,
+        base_file_layer(&paths.config_file)?,
+        &mut included_files,
+    )?;
+
+    if let Some(profile_file) = &paths.profile_file {
+        if !profile_file.exists() {
+            return Err(Error::msg(format!(
+                "profile config file {} does not exist",
+                profile_file.display()
+            )));
+        }
+
+        push_file_layer(&mut layers, file_layer(profile_file), &mut included_files)?;
+    }
+
+    // Environment overrides are matched against the servers as they look once their templates
+    // are applied, so that inherited lists are still split into lists.
+    let mut file_table = build_layered_config(&layers)?.collect()?;
+    let _ = resolve_templates(&mut file_table);
+    layers.extend(environment_layers(&file_table, &ConfigSchema::new()?));
+
+    Ok(layers)
+}
+
+/// Every file the configuration is currently read from, including included files.
+pub fn config_files(paths: &StainlessPaths) -> Vec<PathBuf> {
+    match config_layers(paths) {
+        Ok(layers) => layers.into_iter().filter_map(|layer| layer.file).collect(),
+        Err(_) => {
+            let mut files = vec![paths.config_file.clone()];
+            files.extend(paths.profile_file.clone());
+            files
+        }
+    }
+}
+
+pub fn build_layered_config(layers: &[ConfigLayer]) -> crate::Result<Config> {
+    let sources: Vec<Box<dyn Source + Send + Sync>> = layers
+        .iter()
+        .map(|layer| layer.source.clone_into_box())
+        .collect();
+
+    Ok(Config::builder().add_source(sources).build()?)
+}
+
+pub fn value_sources(layers: &[ConfigLayer]) -> crate::Result<HashMap<String, String>> {
+    let mut sources = HashMap::new();
+
+    for layer in layers {
+        let mut leaf_paths = vec![];
+        collect_leaf_paths("", &layer.source.collect()?, &mut leaf_paths);
+
+        for leaf_path in leaf_paths {
+            sources.insert(leaf_path, layer.name.clone());
+        }
+    }
+
+    Ok(sources)
+}
+
+pub fn render_resolved_config(
+    table: &Map<String, Value>,
+    sources: &HashMap<String, String>,
+) -> String {
+    let mut rendered = String::new();
+    render_table("", 0, table, sources, &mut rendered);
+
+    rendered
+}
+
+pub fn flatten_config(config: &Config) -> crate::Result<BTreeMap<String, String>> {
+    let mut leaves = BTreeMap::new();
+    collect_rendered_leaves("", &config.collect()?, &mut leaves);
+
+    Ok(leaves)
+}
+
+/// Adds a file layer followed by the files it includes. Included files are merged over the file
+/// that includes them in alphabetical order, and may include further files themselves.
+fn push_file_layer(
+    layers: &mut Vec<ConfigLayer>,
+    layer: ConfigLayer,
+    included_files: &mut Vec<PathBuf>,
+) -> crate::Result<()> {
+    let include_files = match &layer.file {
+        Some(file) => find_include_files(file, &layer.source.collect()?)?,
+        None => vec![],
+    };
+    layers.push(layer);
+
+    for include_file in include_files {
+        let canonical_file = include_file.canonicalize()?;
+        if included_files.contains(&canonical_file) {
+            continue;
+        }
+        included_files.push(canonical_file);
+
+        push_file_layer(layers, file_layer(&include_file), included_files)?;
+    }
+
+    Ok(())
+}
+
+fn find_include_files(file: &Path, table: &Map<String, Value>) -> crate::Result<Vec<PathBuf>> {
+    let patterns = match table.get(INCLUDE_KEY).map(|include| &include.kind) {
+        Some(ValueKind::Array(patterns)) => patterns.clone(),
+        Some(ValueKind::String(_)) => vec![table[INCLUDE_KEY].clone()],
+        Some(ValueKind::Nil) | None => return Ok(vec![]),
+        Some(_) => {
+            return Err(Error::msg(format!(
+                "`{}` in {} must be a list of file patterns",
+                INCLUDE_KEY,
+                file.display()
+            )))
+        }
+    };
+    let include_directory = file.parent().unwrap_or_else(|| Path::new(""));
+    let mut include_files = vec![];
+
+    for pattern in patterns {
+        let pattern = include_directory.join(pattern.into_string()?);
+        let pattern = pattern.to_string_lossy();
+        let mut matched_files = glob::glob(&pattern)
+            .map_err(|e| Error::msg(format!("invalid include pattern {}: {}", pattern, e)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        matched_files.sort();
+
+        include_files.extend(matched_files);
+    }
+
+    Ok(include_files)
+}
+
+fn file_layer(path: &Path) -> ConfigLayer {
+    ConfigLayer {
+        name: path.display().to_string(),
+        file: Some(path.to_path_buf()),
+        migrated_from: None,
+        source: Box::new(config::File::from(path)),
+    }
+}
+
+/// The base config file is upgraded to the current config version in memory before it is merged
+/// with the other layers. Only YAML files can be migrated.
+fn base_file_layer(path: &Path) -> crate::Result<ConfigLayer> {
+    let is_yaml = matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("yaml") | Some("yml")
+    );
+    if !is_yaml {
+        return Ok(file_layer(path));
+    }
+
+    let migrated = migrate_document(serde_yaml::from_str(&fs::read_to_string(path)?)?)?;
+    if !migrated.was_migrated() {
+        return Ok(file_layer(path));
+    }
+
+    let name = path.display().to_string();
+    let table = match yaml_to_config_value(&name, &migrated.document).kind {
+        ValueKind::Table(table) => table,
+        _ => Map::new(),
+    };
+
+    Ok(ConfigLayer {
+        name,
+        file: Some(path.to_path_buf()),
+        migrated_from: Some(migrated.original_version),
+        source: Box::new(MigratedFile { table }),
+    })
+}
+
+fn yaml_to_config_value(origin: &String, value: &serde_yaml::Value) -> Value {
+    let kind = match value {
+        serde_yaml::Value::Null => ValueKind::Nil,
+        serde_yaml::Value::Bool(boolean) => ValueKind::Boolean(*boolean),
+        serde_yaml::Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(integer), _) => ValueKind::I64(integer),
+            (None, Some(float)) => ValueKind::Float(float),
+            (None, None) => ValueKind::String(number.to_string()),
+        },
+        serde_yaml::Value::String(string) => ValueKind::String(string.clone()),
+        serde_yaml::Value::Sequence(sequence) => ValueKind::Array(
+            sequence
+                .iter()
+                .map(|element| yaml_to_config_value(origin, element))
+                .collect(),
+        ),
+        serde_yaml::Value::Mapping(mapping) => ValueKind::Table(
+            mapping
+                .iter()
+                .filter_map(|(key, element)| {
+                    let key = match key {
+                        serde_yaml::Value::String(key) => key.clone(),
+                        serde_yaml::Value::Number(key) => key.to_string(),
+                        serde_yaml::Value::Bool(key) => key.to_string(),
+                        _ => return None,
+                    };
+
+                    Some((key, yaml_to_config_value(origin, element)))
+                })
+                .collect(),
+        ),
+    };
+
+    Value::new(Some(origin), kind)
+}
+
+pub fn collect_leaf_paths(prefix: &str, table: &Map<String, Value>, leaf_paths: &mut Vec<String>) {
+    for (key, value) in table {
+        let path = join_path(prefix, key);
+
+        match &value.kind {
+            ValueKind::Table(child_table) => collect_leaf_paths(&path, child_table, leaf_paths),
+            _ => leaf_paths.push(path),
+        }
+    }
+}
+
+fn collect_rendered_leaves(
+    prefix: &str,
+    table: &Map<String, Value>,
+    leaves: &mut BTreeMap<String, String>,
+) {
+    for (key, value) in table {
+        let path = join_path(prefix, key);
+
+        match &value.kind {
+            ValueKind::Table(child_table) => collect_rendered_leaves(&path, child_table, leaves),
+            value => {
+                leaves.insert(path, render_value(value));
+            }
+        }
+    }
+}
+
+fn render_table(
+    prefix: &str,
+    depth: usize,
+    table: &Map<String, Value>,
+    sources: &HashMap<String, String>,
+    rendered: &mut String,
+) {
+    let mut keys: Vec<&String> = table.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let path = join_path(prefix, key);
+        let indent = "  ".repeat(depth);
+
+        match &table[key].kind {
+            ValueKind::Table(child_table) => {
+                rendered.push_str(&format!("{}{}:\n", indent, key));
+                render_table(&path, depth + 1, child_table, sources, rendered);
+            }
+            value => {
+                let source = match sources.get(&path) {
+                    Some(source) => source.as_str(),
+                    None => "unknown",
+                };
+
+                rendered.push_str(&format!(
+                    "{}{}: {}  # from {}\n",
+                    indent,
+                    key,
+                    render_value(value),
+                    source
+                ));
+            }
+        }
+    }
+}
+
+fn render_value(value: &ValueKind) -> String {
+    match value {
+        ValueKind::String(string) => format!("{:?}", string),
+        ValueKind::Array(array) => format!(
+            "[{}]",
+            array
+                .iter()
+                .map(|element| render_value(&element.kind))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        ValueKind::Table(table) => {
+            let mut entries: Vec<String> = table
+                .iter()
+                .map(|(key, element)| format!("{}: {}", key, render_value(&element.kind)))
+                .collect();
+            entries.sort();
+
+            format!("{{{}}}", entries.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Overrides taken from `STAINLESS__`-prefixed environment variables, where `__` separates the
+/// keys of the path being overridden. Keys match the file layers case-insensitively, and values
+/// for list keys are split on whitespace.
+fn environment_layers(existing: &Map<String, Value>, schema: &ConfigSchema) -> Vec<ConfigLayer> {
+    let mut variables: Vec<(String, String)> = std::env::vars()
+        .filter(|(variable, _)| variable.starts_with(ENVIRONMENT_PREFIX))
+        .collect();
+    variables.sort();
+
+    variables
+        .into_iter()
+        .map(|(variable, value)| {
+            let name = format!("environment variable {}", variable);
+            let keys: Vec<&str> = variable[ENVIRONMENT_PREFIX.len()..]
+                .split(ENVIRONMENT_SEPARATOR)
+                .collect();
+            let mut overrides = Map::new();
+
+            insert_override(
+                &mut overrides,
+                Some(existing),
+                schema,
+                Some(schema.root()),
+                &keys,
+                &value,
+                &name,
+            );
+
+            ConfigLayer {
+                name,
+                file: None,
+                migrated_from: None,
+                source: Box::new(EnvironmentOverride { overrides }),
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+struct MigratedFile {
+    table: Map<String, Value>,
+}
+
+impl Source for MigratedFile {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, config::ConfigError> {
+        Ok(self.table.clone())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EnvironmentOverride {
+    overrides: Map<String, Value>,
+}
+
+impl Source for EnvironmentOverride {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, config::ConfigError> {
+        Ok(self.overrides.clone())
+    }
+}
+
+fn insert_override(
+    overrides: &mut Map<String, Value>,
+    existing: Option<&Map<String, Value>>,
+    config_schema: &ConfigSchema,
+    schema: Option<&serde_json::Value>,
+    keys: &[&str],
+    value: &str,
+    origin: &String,
+) {
+    let (key, remaining_keys) = match keys.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    let matching_key = existing
+        .and_then(|table| {
+            table
+                .keys()
+                .find(|existing_key| existing_key.eq_ignore_ascii_case(key))
+        })
+        .cloned()
+        .unwrap_or_else(|| key.to_lowercase());
+    let existing_value = existing.and_then(|table| table.get(&matching_key));
+    let key_schema = schema.and_then(|schema| config_schema.property(schema, key));
+
+    if remaining_keys.is_empty() {
+        let override_value = match existing_value.map(|existing| &existing.kind) {
+            Some(ValueKind::Array(_)) => ValueKind::Array(
+                value
+                    .split_whitespace()
+                    .map(|element| Value::new(Some(origin), element))
+                    .collect(),
+            ),
+            Some(kind) => typed_override_value(kind, value),
+            None => {
+                let instance_type =
+                    key_schema.and_then(|key_schema| config_schema.instance_type(key_schema));
+                typed_override_value(&schema_value_kind(instance_type), value)
+            }
+        };
+
+        overrides.insert(matching_key, Value::new(Some(origin), override_value));
+        return;
+    }
+
+    let existing_table = match existing_value.map(|existing| &existing.kind) {
+        Some(ValueKind::Table(table)) => Some(table),
+        _ => None,
+    };
+    let child = overrides
+        .entry(matching_key)
+        .or_insert_with(|| Value::new(Some(origin), ValueKind::Table(Map::new())));
+
+    if let ValueKind::Table(child_overrides) = &mut child.kind {
+        insert_override(
+            child_overrides,
+            existing_table,
+            config_schema,
+            key_schema,
+            remaining_keys,
+            value,
+            origin,
+        );
+    }
+}
+
+/// Types an override like the value it replaces, so that a number stays a number and a switch stays
+/// a switch whatever reads it. Values that do not parse as that type are kept as strings and left
+/// for deserialization to report.
+fn typed_override_value(existing_kind: &ValueKind, value: &str) -> ValueKind {
+    let typed = match existing_kind {
+        ValueKind::Boolean(_) => value.parse().ok().map(ValueKind::Boolean),
+        ValueKind::I64(_) => value.parse().ok().map(ValueKind::I64),
+        ValueKind::I128(_) => value.parse().ok().map(ValueKind::I128),
+        ValueKind::U64(_) => value.parse().ok().map(ValueKind::U64),
+        ValueKind::U128(_) => value.parse().ok().map(ValueKind::U128),
+        ValueKind::Float(_) => value.parse().ok().map(ValueKind::Float),
+        _ => None,
+    };
+
+    typed.unwrap_or_else(|| ValueKind::String(value.to_string()))
+}
+
+/// Stand-in for the value of a key that is not set anywhere, typed by the schema instead. Keys the
+/// schema does not describe as a number or a switch, such as versions, are kept as strings.
+fn schema_value_kind(instance_type: Option<&str>) -> ValueKind {
+    match instance_type {
+        Some("integer") => ValueKind::I64(0),
+        Some("number") => ValueKind::Float(0.0),
+        Some("boolean") => ValueKind::Boolean(false),
+        _ => ValueKind::Nil,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: Vec<(&str, ValueKind)>) -> ValueKind {
+        let origin = String::from("config.yaml");
+
+        ValueKind::Table(
+            entries
+                .into_iter()
+                .map(|(key, kind)| (key.to_string(), Value::new(Some(&origin), kind)))
+                .collect(),
+        )
+    }
+
+    fn existing_table() -> Map<String, Value> {
+        let restart = table(vec![("max_restarts", ValueKind::I64(5))]);
+        let project = table(vec![
+            ("name", ValueKind::String(String::from("paper"))),
+            ("version", ValueKind::String(String::from("1.18.2"))),
+        ]);
+        let papermc = table(vec![("restart", restart), ("project", project)]);
+        let servers = table(vec![("Lobby", table(vec![("papermc", papermc)]))]);
+
+        match table(vec![("servers", servers)]) {
+            ValueKind::Table(table) => table,
+            _ => unreachable!(),
+        }
+    }
+
+    fn override_value(keys: &[&str], value: &str) -> ValueKind {
+        let schema = ConfigSchema::new().unwrap();
+        let mut table = Map::new();
+        insert_override(
+            &mut table,
+            Some(&existing_table()),
+            &schema,
+            Some(schema.root()),
+            keys,
+            value,
+            &String::from("environment variable"),
+        );
+
+        let (last_key, parent_keys) = keys.split_last().unwrap();
+        for key in parent_keys {
+            table = take_key(table, key).into_table().unwrap();
+        }
+
+        take_key(table, last_key).kind
+    }
+
+    fn take_key(table: Map<String, Value>, key: &str) -> Value {
+        table
+            .into_iter()
+            .find(|(table_key, _)| table_key.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+            .unwrap()
+    }
+
+    #[test]
+    fn types_overrides_like_the_values_they_replace() {
+        assert_eq!(
+            override_value(
+                &["SERVERS", "LOBBY", "PAPERMC", "RESTART", "MAX_RESTARTS"],
+                "10"
+            ),
+            ValueKind::I64(10)
+        );
+        assert_eq!(
+            override_value(
+                &["SERVERS", "LOBBY", "PAPERMC", "PROJECT", "VERSION"],
+                "1.19"
+            ),
+            ValueKind::String(String::from("1.19"))
+        );
+    }
+
+    #[test]
+    fn types_overrides_of_unset_values_by_the_schema() {
+        assert_eq!(
+            override_value(
+                &[
+                    "SERVERS",
+                    "LOBBY",
+                    "PAPERMC",
+                    "RESTART",
+                    "INITIAL_BACKOFF_SECONDS"
+                ],
+                "30"
+            ),
+            ValueKind::I64(30)
+        );
+        assert_eq!(
+            override_value(
+                &[
+                    "SERVERS",
+                    "LOBBY",
+                    "PAPERMC",
+                    "UPDATES",
+                    "RESTART_WHEN_STAGED"
+                ],
+                "true"
+            ),
+            ValueKind::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn keeps_version_like_overrides_of_unset_values_as_strings() {
+        assert_eq!(
+            override_value(&["SERVERS", "ARENA", "VANILLA", "VERSION"], "1.20"),
+            ValueKind::String(String::from("1.20"))
+        );
+    }
+
+    #[test]
+    fn keeps_values_that_do_not_parse_as_strings() {
+        assert_eq!(
+            override_value(
+                &["SERVERS", "LOBBY", "PAPERMC", "RESTART", "MAX_RESTARTS"],
+                "many"
+            ),
+            ValueKind::String(String::from("many"))
+        );
+    }
+}
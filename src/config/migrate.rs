@@ -0,0 +1,242 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Error;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use emoji::symbols::warning::WARNING;
+use serde_yaml::{Mapping, Value};
+
+pub static CURRENT_CONFIG_VERSION: u32 = 2;
+pub static CONFIG_VERSION_KEY: &str = "config_version";
+
+type Migration = fn(&mut Mapping) -> crate::Result<()>;
+
+/// Migrations indexed by the version they upgrade from. Configs written before `config_version`
+/// existed are version 1.
+static MIGRATIONS: &[(u32, Migration)] = &[(1, migrate_single_server_to_server_map)];
+
+pub struct MigratedDocument {
+    pub original_version: u32,
+    pub document: Value,
+}
+
+impl MigratedDocument {
+    pub fn was_migrated(&self) -> bool {
+        self.original_version < CURRENT_CONFIG_VERSION
+    }
+}
+
+pub fn migrate_document(mut document: Value) -> crate::Result<MigratedDocument> {
+    let mapping = match &mut document {
+        Value::Mapping(mapping) => mapping,
+        _ => return Err(Error::msg("configuration file must contain a mapping")),
+    };
+    let original_version = document_version(mapping)?;
+    let mut version = original_version;
+
+    for (from_version, migration) in MIGRATIONS {
+        if version == *from_version {
+            migration(mapping)?;
+            version += 1;
+        }
+    }
+
+    if version != original_version {
+        let mut versioned_mapping = Mapping::new();
+        versioned_mapping.insert(Value::from(CONFIG_VERSION_KEY), Value::from(version as u64));
+        for (key, value) in mapping.iter() {
+            if key.as_str() != Some(CONFIG_VERSION_KEY) {
+                versioned_mapping.insert(key.clone(), value.clone());
+            }
+        }
+        *mapping = versioned_mapping;
+    }
+
+    Ok(MigratedDocument {
+        original_version,
+        document,
+    })
+}
+
+pub fn migrate_config_file(config_file: &Path, write: bool) -> crate::Result<()> {
+    let contents = fs::read_to_string(config_file)?;
+    let migrated = migrate_document(serde_yaml::from_str(&contents)?)?;
+
+    if migrated.original_version > CURRENT_CONFIG_VERSION {
+        return Err(Error::msg(format!(
+            "{} uses config_version {}, which is newer than the newest version this Stainless understands ({}); upgrade Stainless instead",
+            config_file.display(),
+            migrated.original_version,
+            CURRENT_CONFIG_VERSION
+        )));
+    }
+
+    if !migrated.was_migrated() {
+        println!(
+            "{} {} already uses config_version {}",
+            CHECK_MARK.glyph,
+            config_file.display(),
+            CURRENT_CONFIG_VERSION
+        );
+        return Ok(());
+    }
+
+    let migrated_contents = serde_yaml::to_string(&migrated.document)?;
+
+    if !write {
+        println!(
+            "{} {} would be upgraded from config_version {} to {}:",
+            INFORMATION.glyph,
+            config_file.display(),
+            migrated.original_version,
+            CURRENT_CONFIG_VERSION
+        );
+        print!("{}", migrated_contents);
+        println!(
+            "{} Run again with --write to save these changes",
+            INFORMATION.glyph
+        );
+        return Ok(());
+    }
+
+    let backup_file = backup_file_path(config_file, migrated.original_version)?;
+    fs::copy(config_file, &backup_file)?;
+    fs::write(config_file, migrated_contents)?;
+
+    println!(
+        "{} Upgraded {} from config_version {} to {}. The original was saved to {}",
+        CHECK_MARK.glyph,
+        config_file.display(),
+        migrated.original_version,
+        CURRENT_CONFIG_VERSION,
+        backup_file.display()
+    );
+    println!(
+        "{} Comments are not carried over to the upgraded file",
+        WARNING.glyph
+    );
+
+    Ok(())
+}
+
+fn document_version(mapping: &Mapping) -> crate::Result<u32> {
+    match mapping.get(&Value::from(CONFIG_VERSION_KEY)) {
+        Some(version) => match version.as_u64() {
+            Some(version) if version > 0 && version <= u32::MAX as u64 => Ok(version as u32),
+            _ => Err(Error::msg(format!(
+                "{} must be a positive whole number",
+                CONFIG_VERSION_KEY
+            ))),
+        },
+        None => Ok(1),
+    }
+}
+
+fn backup_file_path(config_file: &Path, version: u32) -> crate::Result<PathBuf> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let mut backup_file_name = config_file
+        .file_name()
+        .ok_or_else(|| Error::msg("config file path has no file name"))?
+        .to_os_string();
+    backup_file_name.push(format!(".v{}-{}.bak", version, timestamp));
+
+    Ok(config_file.with_file_name(backup_file_name))
+}
+
+/// Version 1 configured one server under `server` and named it with `server_name`. Version 2
+/// configures a map of servers under `servers`, keyed by name.
+fn migrate_single_server_to_server_map(mapping: &mut Mapping) -> crate::Result<()> {
+    let mut server = match mapping.remove(&Value::from("server")) {
+        Some(server) => server,
+        None => return Ok(()),
+    };
+
+    let mut server_name = String::from("server");
+    if let Value::Mapping(backends) = &mut server {
+        for (_, backend) in backends.iter_mut() {
+            if let Value::Mapping(backend) = backend {
+                if let Some(Value::String(name)) = backend.remove(&Value::from("server_name")) {
+                    server_name = name;
+                }
+            }
+        }
+    }
+
+    let mut servers = match mapping.remove(&Value::from("servers")) {
+        Some(Value::Mapping(servers)) => servers,
+        _ => Mapping::new(),
+    };
+    servers.insert(Value::from(server_name), server);
+    mapping.insert(Value::from("servers"), Value::Mapping(servers));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrate(yaml: &str) -> MigratedDocument {
+        migrate_document(serde_yaml::from_str(yaml).unwrap()).unwrap()
+    }
+
+    fn yaml(yaml: &str) -> Value {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn migrates_a_single_server_to_a_server_map() {
+        let migrated =
+            migrate("server:\n  papermc:\n    server_name: Lobby\n    jvm_arguments: [-Xmx1G]\n");
+
+        assert_eq!(migrated.original_version, 1);
+        assert!(migrated.was_migrated());
+        assert_eq!(
+            migrated.document,
+            yaml("config_version: 2\nservers:\n  Lobby:\n    papermc:\n      jvm_arguments: [-Xmx1G]\n")
+        );
+    }
+
+    #[test]
+    fn names_an_unnamed_server_after_its_key() {
+        let migrated = migrate("server:\n  vanilla:\n    version: 1.18.2\n");
+
+        assert_eq!(
+            migrated.document,
+            yaml("config_version: 2\nservers:\n  server:\n    vanilla:\n      version: 1.18.2\n")
+        );
+    }
+
+    #[test]
+    fn leaves_current_configs_alone() {
+        let document =
+            "config_version: 2\nservers:\n  Lobby:\n    vanilla:\n      version: 1.18.2\n";
+        let migrated = migrate(document);
+
+        assert_eq!(migrated.original_version, CURRENT_CONFIG_VERSION);
+        assert!(!migrated.was_migrated());
+        assert_eq!(migrated.document, yaml(document));
+    }
+
+    #[test]
+    fn reports_newer_configs_unchanged() {
+        let migrated = migrate("config_version: 3\nservers: {}\n");
+
+        assert_eq!(migrated.original_version, 3);
+        assert!(!migrated.was_migrated());
+    }
+
+    #[test]
+    fn rejects_invalid_config_versions() {
+        for document in [
+            "config_version: 0",
+            "config_version: two",
+            "config_version: -1",
+        ] {
+            assert!(migrate_document(yaml(document)).is_err(), "{}", document);
+        }
+        assert!(migrate_document(yaml("- server")).is_err());
+    }
+}
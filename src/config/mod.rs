@@ -1,116 +1,428 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{ErrorKind, Write};
-use std::path::Path;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
 use anyhow::Error;
-use config::Config;
+use config::Source;
 use emoji::symbols::alphanum::INFORMATION;
 use emoji::symbols::other_symbol::CHECK_MARK;
 use emoji::symbols::warning::WARNING;
-use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::Client;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::config::constants::{
-    DOWNLOAD_PROGRESS_BAR_TEMPLATE, SERVER_INFO_DIR_PATH, STAINLESS_CONFIG_PATH,
-    STAINLESS_DEFAULT_CONFIG_URL,
-};
+use crate::config::constants::{SERVER_DIRECTORIES_DIR_NAME, STAINLESS_CONFIG_FILE_NAME};
+use crate::config::init::InitAnswers;
+use crate::custom::CustomServer;
+use crate::fabric::FabricServer;
+use crate::forge::ForgeServer;
+use crate::fork::ForkServer;
+use crate::server::restart::RestartPolicy;
+use crate::server::{Server, ServerSettings};
+use crate::vanilla::VanillaServer;
 use crate::PaperMCServer;
 
 pub mod constants;
+mod init;
+mod layers;
+mod migrate;
+pub mod reload;
+mod schema;
+mod templates;
+mod validate;
 
 pub type StainlessConfig = Stainless;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone)]
+pub struct StainlessPaths {
+    pub home: PathBuf,
+    pub config_file: PathBuf,
+    pub profile_file: Option<PathBuf>,
+}
+
+impl StainlessPaths {
+    pub fn new(
+        home: PathBuf,
+        config_file: Option<PathBuf>,
+        profile: Option<String>,
+    ) -> StainlessPaths {
+        let config_file = match config_file {
+            Some(config_file) => config_file,
+            None => home.join(STAINLESS_CONFIG_FILE_NAME),
+        };
+        let profile_file = profile.map(|profile| profile_config_file(&config_file, &profile));
+
+        StainlessPaths {
+            home,
+            config_file,
+            profile_file,
+        }
+    }
+
+    pub fn default_server_directory(&self, server_name: &str) -> PathBuf {
+        self.home
+            .join(SERVER_DIRECTORIES_DIR_NAME)
+            .join(server_name)
+    }
+}
+
+/// Configuration of every server managed by Stainless.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct Stainless {
-    pub server: ServerType,
+    /// Version of the configuration format. Older configurations are upgraded when loaded.
+    pub config_version: u32,
+    /// Patterns of further configuration files to merge over this one, such as `conf.d/*.yaml`.
+    /// Patterns are relative to the directory of the file that includes them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Reusable server settings, keyed by template name. A server or template that sets
+    /// `template: <name>` inherits every setting of that template and can override each of them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, serde_json::Value>,
+    /// Servers to manage, keyed by server name.
+    pub servers: BTreeMap<String, ServerType>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Backend that provides a server's application. Exactly one backend is set per server.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ServerType {
+    /// Runs the latest build of a PaperMC project version, downloaded from papermc.io.
     PaperMC(PaperMCServer),
+    /// Runs an unmodified Minecraft server of a version, or of the newest release or snapshot,
+    /// downloaded from Mojang.
+    Vanilla(VanillaServer),
+    /// Runs a Fabric server of a Minecraft version with the newest or a pinned loader, set up by
+    /// the launcher from meta.fabricmc.net.
+    Fabric(FabricServer),
+    /// Runs a Forge or NeoForge server of a Minecraft version, set up by running the installer from
+    /// the project's Maven repository.
+    Forge(ForgeServer),
+    /// Runs the latest build of a fork of Paper, such as Purpur, downloaded through the fork's own
+    /// API.
+    Fork(ForkServer),
+    /// Runs a jar that no API publishes, from a local path or from a URL with its expected hash.
+    Custom(CustomServer),
+}
+
+impl Stainless {
+    pub fn select_servers(&self, server_names: &[String]) -> crate::Result<Stainless> {
+        if server_names.is_empty() {
+            return Ok(self.clone());
+        }
+
+        let mut servers = BTreeMap::new();
+
+        for name in server_names {
+            match self.servers.get(name) {
+                Some(server) => {
+                    servers.insert(name.clone(), server.clone());
+                }
+                None => {
+                    return Err(Error::msg(format!(
+                        "no server named {} is configured",
+                        name
+                    )))
+                }
+            }
+        }
+
+        Ok(Stainless {
+            servers,
+            ..self.clone()
+        })
+    }
 }
 
-pub async fn load_stainless_config(http_client: &Client) -> crate::Result<StainlessConfig> {
-    generate_stainless_files_and_directories(http_client).await?;
+impl ServerType {
+    /// Key of the backend in the configuration, such as `papermc`.
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            ServerType::PaperMC(_) => "papermc",
+            ServerType::Vanilla(_) => "vanilla",
+            ServerType::Fabric(_) => "fabric",
+            ServerType::Forge(_) => "forge",
+            ServerType::Fork(_) => "fork",
+            ServerType::Custom(_) => "custom",
+        }
+    }
+
+    pub fn settings(&self) -> &ServerSettings {
+        match self {
+            ServerType::PaperMC(server) => &server.settings,
+            ServerType::Vanilla(server) => &server.settings,
+            ServerType::Fabric(server) => &server.settings,
+            ServerType::Forge(server) => &server.settings,
+            ServerType::Fork(server) => &server.settings,
+            ServerType::Custom(server) => &server.settings,
+        }
+    }
+
+    fn settings_mut(&mut self) -> &mut ServerSettings {
+        match self {
+            ServerType::PaperMC(server) => &mut server.settings,
+            ServerType::Vanilla(server) => &mut server.settings,
+            ServerType::Fabric(server) => &mut server.settings,
+            ServerType::Forge(server) => &mut server.settings,
+            ServerType::Fork(server) => &mut server.settings,
+            ServerType::Custom(server) => &mut server.settings,
+        }
+    }
+
+    pub fn working_directory(&self) -> &Path {
+        &self.settings().resolved_working_directory
+    }
+
+    pub fn stop_command(&self) -> &str {
+        match self {
+            ServerType::PaperMC(server) => server.stop_command(),
+            ServerType::Vanilla(server) => server.stop_command(),
+            ServerType::Fabric(server) => server.stop_command(),
+            ServerType::Forge(server) => server.stop_command(),
+            ServerType::Fork(server) => server.stop_command(),
+            ServerType::Custom(server) => server.stop_command(),
+        }
+    }
+
+    pub fn save_command(&self) -> Option<&str> {
+        match self {
+            ServerType::PaperMC(server) => server.save_command(),
+            ServerType::Vanilla(server) => server.save_command(),
+            ServerType::Fabric(server) => server.save_command(),
+            ServerType::Forge(server) => server.save_command(),
+            ServerType::Fork(server) => server.save_command(),
+            ServerType::Custom(server) => server.save_command(),
+        }
+    }
+
+    pub fn broadcast_command(&self) -> Option<&str> {
+        match self {
+            ServerType::PaperMC(server) => server.broadcast_command(),
+            ServerType::Vanilla(server) => server.broadcast_command(),
+            ServerType::Fabric(server) => server.broadcast_command(),
+            ServerType::Forge(server) => server.broadcast_command(),
+            ServerType::Fork(server) => server.broadcast_command(),
+            ServerType::Custom(server) => server.broadcast_command(),
+        }
+    }
+
+    pub fn restart_policy(&self) -> &RestartPolicy {
+        &self.settings().restart
+    }
+
+    fn resolve(&mut self, name: &str, paths: &StainlessPaths) {
+        let settings = self.settings_mut();
+
+        settings.server_name = name.to_string();
+        settings.resolved_working_directory = match &settings.working_directory {
+            Some(directory) => paths.home.join(directory),
+            None => paths.default_server_directory(name),
+        };
+
+        if let ServerType::Custom(server) = self {
+            server.jar.resolved_path = server.jar.path.as_ref().map(|path| paths.home.join(path));
+        }
+    }
+}
+
+/// Loads the configuration. Only commands that run servers generate a missing config file; every
+/// other command fails without one, rather than reporting on servers nobody configured.
+pub fn load_stainless_config(
+    paths: &StainlessPaths,
+    generate_missing: bool,
+) -> crate::Result<StainlessConfig> {
+    if generate_missing {
+        generate_stainless_files_and_directories(paths)?;
+    } else if !paths.config_file.exists() {
+        return Err(Error::msg(format!(
+            "config file {} not found; run `stainless init` to create one",
+            paths.config_file.display()
+        )));
+    }
 
     println!("{} Loading server configuration...", INFORMATION.glyph);
 
-    let config: StainlessConfig = Config::builder()
-        .add_source(config::File::with_name(STAINLESS_CONFIG_PATH))
-        .build()?
-        .try_deserialize()?;
+    let config = read_stainless_config(paths)?;
 
-    println!("{} Stainless configuration loaded!", CHECK_MARK.glyph);
+    println!(
+        "{} Stainless configuration loaded with {} server(s)!",
+        CHECK_MARK.glyph,
+        config.servers.len()
+    );
 
     Ok(config)
 }
 
-async fn generate_stainless_files_and_directories(http_client: &Client) -> crate::Result<()> {
+pub fn read_stainless_config(paths: &StainlessPaths) -> crate::Result<StainlessConfig> {
+    let layers = layers::config_layers(paths)?;
+    let (config, issues) = validate::validate_layers(&layers);
+    validate::report_issues(&issues)?;
+
+    let mut config = match config {
+        Some(config) => config,
+        None => return Err(Error::msg("configuration could not be read")),
+    };
+
+    for (name, server) in config.servers.iter_mut() {
+        server.resolve(name, paths);
+        fs::create_dir_all(server.working_directory())?;
+    }
+
+    Ok(config)
+}
+
+pub async fn validate_stainless_config_online(
+    paths: &StainlessPaths,
+    config: &StainlessConfig,
+    http_client: &Client,
+) -> crate::Result<()> {
     println!(
-        "{} Generating any missing Stainless files or directories...",
+        "{} Checking configured server versions online...",
         INFORMATION.glyph
     );
 
-    generate_stainless_directories()?;
-    generate_stainless_config_file_if_needed(http_client).await?;
+    let layers = layers::config_layers(paths)?;
+    let issues = validate::validate_projects_online(&layers, config, http_client).await;
+
+    validate::report_issues(&issues)
+}
+
+pub fn migrate_stainless_config(paths: &StainlessPaths, write: bool) -> crate::Result<()> {
+    migrate::migrate_config_file(&paths.config_file, write)
+}
+
+pub fn show_stainless_config(paths: &StainlessPaths, resolved: bool) -> crate::Result<()> {
+    if !resolved {
+        print!("{}", fs::read_to_string(&paths.config_file)?);
+
+        return Ok(());
+    }
+
+    let layers = layers::config_layers(paths)?;
+    let mut config = layers::build_layered_config(&layers)?.collect()?;
+    let mut sources =
+        templates::resolve_templates(&mut config).map_err(|e| Error::msg(e.to_string()))?;
+    sources.extend(layers::value_sources(&layers)?);
+
+    println!(
+        "# Resolved from: {}",
+        layers
+            .iter()
+            .map(|layer| layer.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    print!("{}", layers::render_resolved_config(&config, &sources));
+
+    Ok(())
+}
+
+pub fn write_stainless_config_schema(output: Option<&Path>) -> crate::Result<()> {
+    let schema = schema::stainless_config_schema()?;
+
+    match output {
+        Some(output) => {
+            fs::write(output, schema)?;
+            println!(
+                "{} Wrote configuration schema to {}",
+                CHECK_MARK.glyph,
+                output.display()
+            );
+        }
+        None => println!("{}", schema),
+    }
 
     Ok(())
 }
 
-fn generate_stainless_directories() -> crate::Result<()> {
-    std::fs::create_dir_all(SERVER_INFO_DIR_PATH)?;
+pub async fn init_stainless_config(
+    http_client: &Client,
+    paths: &StainlessPaths,
+    overwrite: bool,
+    use_defaults: bool,
+) -> crate::Result<()> {
+    if paths.config_file.exists() && !overwrite {
+        return Err(Error::msg(format!(
+            "config file {} already exists; use --force to overwrite it",
+            paths.config_file.display()
+        )));
+    }
+
+    let answers = if use_defaults {
+        InitAnswers::default()
+    } else {
+        init::ask_init_questions(http_client).await?
+    };
+
+    generate_stainless_directories(paths)?;
+    generate_new_stainless_config_file(&paths.config_file, &answers)
+}
+
+fn generate_stainless_files_and_directories(paths: &StainlessPaths) -> crate::Result<()> {
+    println!(
+        "{} Generating any missing Stainless files or directories...",
+        INFORMATION.glyph
+    );
+
+    generate_stainless_directories(paths)?;
+    generate_stainless_config_file_if_needed(&paths.config_file)?;
 
     Ok(())
 }
 
-async fn generate_stainless_config_file_if_needed(http_client: &Client) -> crate::Result<()> {
-    let config_path = Path::new(STAINLESS_CONFIG_PATH);
+fn generate_stainless_directories(paths: &StainlessPaths) -> crate::Result<()> {
+    std::fs::create_dir_all(paths.home.join(SERVER_DIRECTORIES_DIR_NAME))?;
+
+    Ok(())
+}
 
+fn generate_stainless_config_file_if_needed(config_path: &Path) -> crate::Result<()> {
     if let Err(e) = fs::File::open(config_path) {
         if e.kind() == ErrorKind::NotFound {
             println!("{} Could not find existing config file.", WARNING.glyph);
 
-            generate_new_stainless_config_file(http_client, config_path).await?
+            generate_new_stainless_config_file(config_path, &InitAnswers::default())?
         }
     }
 
     Ok(())
 }
 
-async fn generate_new_stainless_config_file(
-    http_client: &Client,
+fn generate_new_stainless_config_file(
     config_path: &Path,
+    answers: &InitAnswers,
 ) -> crate::Result<()> {
     println!(
         "{} Attempting to create new config file...",
         INFORMATION.glyph
     );
 
-    let mut config_file = fs::File::create(config_path)?;
-    let mut default_config_response = http_client.get(STAINLESS_DEFAULT_CONFIG_URL).send().await?;
-
-    let content_length = match default_config_response.content_length() {
-        Some(len) => len,
-        None => return Err(Error::msg("no content delivered for download")),
-    };
-    let progress_bar = ProgressBar::new(content_length);
-    progress_bar.set_style(ProgressStyle::default_bar().template(DOWNLOAD_PROGRESS_BAR_TEMPLATE)?);
-
-    progress_bar.set_message("Downloading...");
-    while let Some(chunk) = default_config_response.chunk().await? {
-        progress_bar.inc(chunk.len() as u64);
-        config_file.write_all(&chunk)?;
+    if let Some(config_dir) = config_path.parent() {
+        fs::create_dir_all(config_dir)?;
     }
 
-    config_file.flush()?;
+    fs::write(config_path, init::render_config(answers))?;
 
-    progress_bar.finish_with_message("Done");
     println!(
-        "{} Successfully created new configuration file!",
-        CHECK_MARK.glyph
+        "{} Successfully created new configuration file at {}!",
+        CHECK_MARK.glyph,
+        config_path.display()
     );
 
     Ok(())
 }
+
+fn profile_config_file(config_file: &Path, profile: &str) -> PathBuf {
+    let file_stem = config_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_name = match config_file.extension() {
+        Some(extension) => format!("{}.{}.{}", file_stem, profile, extension.to_string_lossy()),
+        None => format!("{}.{}", file_stem, profile),
+    };
+
+    config_file.with_file_name(file_name)
+}
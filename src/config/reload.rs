@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use config::Config;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use emoji::symbols::warning::WARNING;
+use tokio::sync::watch::{Receiver, Sender};
+
+use crate::config::layers::{config_files, flatten_config};
+use crate::config::{read_stainless_config, Stainless, StainlessPaths};
+
+static CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub type ConfigUpdates = Receiver<Arc<Stainless>>;
+
+pub fn watch_stainless_config(
+    paths: StainlessPaths,
+    selected_servers: Vec<String>,
+    config: Stainless,
+) -> ConfigUpdates {
+    let (config_sender, config_receiver) = tokio::sync::watch::channel(Arc::new(config));
+
+    tokio::spawn(poll_config_changes(paths, selected_servers, config_sender));
+
+    config_receiver
+}
+
+async fn poll_config_changes(
+    paths: StainlessPaths,
+    selected_servers: Vec<String>,
+    config_sender: Sender<Arc<Stainless>>,
+) {
+    let mut last_modified = config_files_modified(&paths);
+    let mut interval = tokio::time::interval(CONFIG_POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let modified = config_files_modified(&paths);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        println!(
+            "{} Configuration file changed, validating...",
+            INFORMATION.glyph
+        );
+
+        let updated_config = read_stainless_config(&paths)
+            .and_then(|config| config.select_servers(&selected_servers));
+
+        match updated_config {
+            Ok(updated_config) => {
+                let current_config = config_sender.borrow().clone();
+                if *current_config == updated_config {
+                    println!("{} Configuration is unchanged", INFORMATION.glyph);
+                    continue;
+                }
+
+                log_config_changes(&current_config, &updated_config);
+
+                if config_sender.send(Arc::new(updated_config)).is_err() {
+                    return;
+                }
+            }
+            Err(e) => println!(
+                "{} Rejected configuration change, keeping the current configuration: {}",
+                WARNING.glyph, e
+            ),
+        }
+    }
+}
+
+fn config_files_modified(paths: &StainlessPaths) -> Vec<(PathBuf, Option<SystemTime>)> {
+    config_files(paths)
+        .into_iter()
+        .map(|file| {
+            let modified = fs::metadata(&file)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+
+            (file, modified)
+        })
+        .collect()
+}
+
+fn log_config_changes(current_config: &Stainless, updated_config: &Stainless) {
+    let (current_values, updated_values) = match (
+        flatten_stainless(current_config),
+        flatten_stainless(updated_config),
+    ) {
+        (Ok(current_values), Ok(updated_values)) => (current_values, updated_values),
+        _ => {
+            println!(
+                "{} Configuration updated, but its changes could not be listed",
+                WARNING.glyph
+            );
+            return;
+        }
+    };
+
+    println!(
+        "{} Configuration updated; servers apply it at their next restart:",
+        CHECK_MARK.glyph
+    );
+
+    for (key, current_value) in &current_values {
+        match updated_values.get(key) {
+            Some(updated_value) if updated_value != current_value => {
+                println!("    {}: {} -> {}", key, current_value, updated_value)
+            }
+            Some(_) => {}
+            None => println!("    {}: {} -> (removed)", key, current_value),
+        }
+    }
+
+    for (key, updated_value) in &updated_values {
+        if !current_values.contains_key(key) {
+            println!("    {}: (added) -> {}", key, updated_value);
+        }
+    }
+
+    for name in updated_config.servers.keys() {
+        if !current_config.servers.contains_key(name) {
+            println!(
+                "{} Server {} was added; restart Stainless to start it",
+                WARNING.glyph, name
+            );
+        }
+    }
+}
+
+fn flatten_stainless(config: &Stainless) -> crate::Result<BTreeMap<String, String>> {
+    flatten_config(&Config::try_from(config)?)
+}
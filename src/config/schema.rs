@@ -0,0 +1,324 @@
+use schemars::schema_for;
+use serde_json::{json, Map, Value};
+
+use crate::config::Stainless;
+
+static TEMPLATE_PROPERTY: &str = "template";
+static DEFINITIONS_PREFIX: &str = "#/definitions/";
+
+pub fn stainless_config_schema() -> crate::Result<String> {
+    let mut schema = serde_json::to_value(schema_for!(Stainless))?;
+    add_server_template_property(&mut schema);
+
+    Ok(serde_json::to_string_pretty(&schema)?)
+}
+
+/// The `template` key of a server is expanded before servers are deserialized, so it is not part
+/// of `ServerType` and has to be added by hand. A server that sets it may leave out any setting,
+/// including its backend, since the template can provide them, so each backend is also accepted
+/// in a form where nothing is required.
+fn add_server_template_property(schema: &mut Value) {
+    let variants = match schema["definitions"]["ServerType"]["oneOf"].as_array() {
+        Some(variants) => variants.clone(),
+        None => return,
+    };
+    let definitions = match schema["definitions"].as_object() {
+        Some(definitions) => definitions.clone(),
+        None => return,
+    };
+
+    let mut inherited_definitions = Map::new();
+    let mut template_variants = vec![];
+    for variant in &variants {
+        let mut template_variant = variant.clone();
+        relax_required(
+            &mut template_variant,
+            &definitions,
+            &mut inherited_definitions,
+        );
+        template_variant["required"] = json!([TEMPLATE_PROPERTY]);
+        if let Some(properties) = template_variant["properties"].as_object_mut() {
+            properties.insert(
+                String::from(TEMPLATE_PROPERTY),
+                json!({
+                    "description": "Name of the template this server inherits its settings from.",
+                    "type": "string"
+                }),
+            );
+        }
+
+        template_variants.push(template_variant);
+    }
+
+    // A server setting only `template` matches the form of every backend, so the forms are
+    // combined with `anyOf` rather than `oneOf`.
+    let server_type = &mut schema["definitions"]["ServerType"];
+    if let Some(server_type) = server_type.as_object_mut() {
+        server_type.remove("oneOf");
+        server_type.insert(
+            String::from("anyOf"),
+            Value::Array(variants.into_iter().chain(template_variants).collect()),
+        );
+    }
+    if let Some(definitions) = schema["definitions"].as_object_mut() {
+        definitions.extend(inherited_definitions);
+    }
+}
+
+/// Drops `required` from an object schema and the objects nested in it, pointing references at
+/// `Inherited` copies of their definitions that are relaxed the same way. The variants of `oneOf`
+/// are left alone, since their required keys are what tells them apart.
+fn relax_required(
+    schema: &mut Value,
+    definitions: &Map<String, Value>,
+    inherited_definitions: &mut Map<String, Value>,
+) {
+    let schema = match schema.as_object_mut() {
+        Some(schema) => schema,
+        None => return,
+    };
+
+    schema.remove("required");
+
+    if let Some(Value::String(reference)) = schema.get_mut("$ref") {
+        if let Some(name) = reference
+            .strip_prefix(DEFINITIONS_PREFIX)
+            .map(str::to_string)
+        {
+            let inherited_name = format!("Inherited{}", name);
+
+            if !inherited_definitions.contains_key(&inherited_name) {
+                let mut definition = definitions.get(&name).cloned().unwrap_or(Value::Null);
+                // Placeholder, so that a definition that refers to itself ends here.
+                inherited_definitions.insert(inherited_name.clone(), Value::Null);
+                relax_required(&mut definition, definitions, inherited_definitions);
+
+                if Some(&definition) == definitions.get(&name) {
+                    inherited_definitions.remove(&inherited_name);
+                } else {
+                    inherited_definitions.insert(inherited_name.clone(), definition);
+                }
+            }
+
+            if inherited_definitions.contains_key(&inherited_name) {
+                *reference = format!("{}{}", DEFINITIONS_PREFIX, inherited_name);
+            }
+        }
+    }
+
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        for property in properties.values_mut() {
+            relax_required(property, definitions, inherited_definitions);
+        }
+    }
+    for key in ["allOf", "anyOf"] {
+        if let Some(Value::Array(subschemas)) = schema.get_mut(key) {
+            for subschema in subschemas {
+                relax_required(subschema, definitions, inherited_definitions);
+            }
+        }
+    }
+    for key in ["items", "additionalProperties"] {
+        if let Some(subschema) = schema.get_mut(key) {
+            relax_required(subschema, definitions, inherited_definitions);
+        }
+    }
+}
+
+/// The configuration schema, used to look up what type a key holds when the configuration itself
+/// does not say, such as for environment overrides of keys that are not set in any file.
+pub struct ConfigSchema {
+    root: Value,
+}
+
+impl ConfigSchema {
+    pub fn new() -> crate::Result<ConfigSchema> {
+        Ok(ConfigSchema {
+            root: serde_json::to_value(schema_for!(Stainless))?,
+        })
+    }
+
+    pub fn root(&self) -> &Value {
+        &self.root
+    }
+
+    /// Schema of the property `key` of an object schema. Keys match case-insensitively, and
+    /// properties of the subschemas it is combined from are searched as well.
+    pub fn property<'a>(&'a self, schema: &'a Value, key: &str) -> Option<&'a Value> {
+        let schema = self.resolve(schema);
+
+        let property = schema["properties"].as_object().and_then(|properties| {
+            properties
+                .iter()
+                .find(|(property, _)| property.eq_ignore_ascii_case(key))
+                .map(|(_, property_schema)| property_schema)
+        });
+        if property.is_some() {
+            return property;
+        }
+
+        let from_subschemas = self
+            .subschemas(schema)
+            .find_map(|subschema| self.property(subschema, key));
+        if from_subschemas.is_some() {
+            return from_subschemas;
+        }
+
+        schema
+            .get("additionalProperties")
+            .filter(|additional_properties| additional_properties.is_object())
+    }
+
+    /// JSON type of the values a schema accepts, such as `integer` or `boolean`, ignoring `null`.
+    pub fn instance_type<'a>(&'a self, schema: &'a Value) -> Option<&'a str> {
+        let schema = self.resolve(schema);
+
+        let instance_type = match &schema["type"] {
+            Value::String(instance_type) => Some(instance_type.as_str()),
+            Value::Array(instance_types) => instance_types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|instance_type| *instance_type != "null"),
+            _ => None,
+        };
+
+        instance_type.or_else(|| {
+            self.subschemas(schema)
+                .find_map(|subschema| self.instance_type(subschema))
+        })
+    }
+
+    fn resolve<'a>(&'a self, schema: &'a Value) -> &'a Value {
+        match schema["$ref"]
+            .as_str()
+            .and_then(|reference| reference.strip_prefix(DEFINITIONS_PREFIX))
+        {
+            Some(definition) => &self.root["definitions"][definition],
+            None => schema,
+        }
+    }
+
+    fn subschemas<'a>(&'a self, schema: &'a Value) -> impl Iterator<Item = &'a Value> {
+        ["allOf", "anyOf", "oneOf"]
+            .into_iter()
+            .filter_map(move |combinator| schema[combinator].as_array())
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks an instance against the keywords the configuration schema uses.
+    fn accepts(root: &Value, schema: &Value, instance: &Value) -> bool {
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.trim_start_matches(DEFINITIONS_PREFIX);
+            return accepts(root, &root["definitions"][name], instance);
+        }
+
+        let type_matches = |instance_type: &str| match instance_type {
+            "object" => instance.is_object(),
+            "array" => instance.is_array(),
+            "string" => instance.is_string(),
+            "integer" => instance.is_i64() || instance.is_u64(),
+            "number" => instance.is_number(),
+            "boolean" => instance.is_boolean(),
+            "null" => instance.is_null(),
+            _ => true,
+        };
+        let types_match = match &schema["type"] {
+            Value::String(instance_type) => type_matches(instance_type),
+            Value::Array(instance_types) => instance_types
+                .iter()
+                .any(|instance_type| type_matches(instance_type.as_str().unwrap_or_default())),
+            _ => true,
+        };
+        if !types_match {
+            return false;
+        }
+
+        if let Some(values) = schema["enum"].as_array() {
+            if !values.contains(instance) {
+                return false;
+            }
+        }
+
+        let subschemas = |key: &str| schema[key].as_array().cloned().unwrap_or_default();
+        let matching = |key: &str| {
+            subschemas(key)
+                .iter()
+                .filter(|subschema| accepts(root, subschema, instance))
+                .count()
+        };
+        if matching("allOf") != subschemas("allOf").len()
+            || (!subschemas("anyOf").is_empty() && matching("anyOf") == 0)
+            || (!subschemas("oneOf").is_empty() && matching("oneOf") != 1)
+        {
+            return false;
+        }
+
+        let object = match instance.as_object() {
+            Some(object) => object,
+            None => return true,
+        };
+        let required = schema["required"].as_array().cloned().unwrap_or_default();
+        if required
+            .iter()
+            .any(|key| !object.contains_key(key.as_str().unwrap_or_default()))
+        {
+            return false;
+        }
+
+        object.iter().all(|(key, value)| {
+            match (
+                schema["properties"].get(key),
+                &schema["additionalProperties"],
+            ) {
+                (Some(property), _) => accepts(root, property, value),
+                (None, Value::Bool(false)) => false,
+                (None, additional_properties @ Value::Object(_)) => {
+                    accepts(root, additional_properties, value)
+                }
+                (None, _) => true,
+            }
+        })
+    }
+
+    fn accepts_server(server: Value) -> bool {
+        let schema: Value = serde_json::from_str(&stainless_config_schema().unwrap()).unwrap();
+
+        accepts(
+            &schema,
+            &schema,
+            &json!({ "config_version": 2, "servers": { "Lobby": server } }),
+        )
+    }
+
+    #[test]
+    fn accepts_servers_that_only_name_a_template() {
+        assert!(accepts_server(json!({ "template": "paper" })));
+        assert!(accepts_server(json!({
+            "template": "paper",
+            "papermc": { "project": { "version": "1.18.2" } }
+        })));
+    }
+
+    #[test]
+    fn requires_every_setting_of_servers_without_a_template() {
+        assert!(accepts_server(json!({
+            "papermc": {
+                "project": { "name": "paper", "version": "1.18.2" },
+                "jvm_arguments": ["-Xmx2G"]
+            }
+        })));
+        assert!(!accepts_server(json!({
+            "papermc": { "project": { "name": "paper", "version": "1.18.2" } }
+        })));
+        assert!(!accepts_server(json!({
+            "template": "paper",
+            "papermc": { "project": { "name": "paper" }, "jvm_arguments": [] },
+            "vanilla": { "version": "1.18.2" }
+        })));
+    }
+}
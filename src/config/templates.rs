@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use config::{Map, Value, ValueKind};
+
+use crate::config::layers::collect_leaf_paths;
+
+static TEMPLATES_KEY: &str = "templates";
+static TEMPLATE_KEY: &str = "template";
+
+pub struct TemplateError {
+    pub key: String,
+    pub message: String,
+}
+
+impl TemplateError {
+    fn new(key: String, message: String) -> TemplateError {
+        TemplateError { key, message }
+    }
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`: {}", self.key, self.message)
+    }
+}
+
+/// Replaces the `template` key of every server with the values it inherits from that template,
+/// keeping the values the server sets itself. Returns which template each inherited value came
+/// from, keyed by its full path.
+pub fn resolve_templates(
+    table: &mut Map<String, Value>,
+) -> Result<HashMap<String, String>, TemplateError> {
+    let templates = match table.get(TEMPLATES_KEY).map(|templates| &templates.kind) {
+        Some(ValueKind::Table(templates)) => templates.clone(),
+        Some(ValueKind::Nil) | None => Map::new(),
+        Some(_) => {
+            return Err(TemplateError::new(
+                String::from(TEMPLATES_KEY),
+                String::from("must map template names to server settings"),
+            ))
+        }
+    };
+
+    let servers = match table.get_mut("servers").map(|servers| &mut servers.kind) {
+        Some(ValueKind::Table(servers)) => servers,
+        _ => return Ok(HashMap::new()),
+    };
+
+    let mut template_sources = HashMap::new();
+
+    for (name, server) in servers.iter_mut() {
+        let server_table = match &mut server.kind {
+            ValueKind::Table(server_table) => server_table,
+            _ => continue,
+        };
+        let template_key = format!("servers.{}.{}", name, TEMPLATE_KEY);
+        let template_name = match server_table.remove(TEMPLATE_KEY) {
+            Some(template_name) => template_value_name(&template_key, template_name)?,
+            None => continue,
+        };
+
+        let inherited = expand_template(&templates, &template_name, &template_key, &mut vec![])?;
+
+        let mut inherited_paths = vec![];
+        collect_leaf_paths(
+            &format!("servers.{}", name),
+            &inherited,
+            &mut inherited_paths,
+        );
+        for inherited_path in inherited_paths {
+            template_sources.insert(inherited_path, format!("template {}", template_name));
+        }
+
+        *server_table = merge_tables(inherited, server_table.clone());
+    }
+
+    Ok(template_sources)
+}
+
+fn expand_template(
+    templates: &Map<String, Value>,
+    template_name: &str,
+    key: &str,
+    expanding: &mut Vec<String>,
+) -> Result<Map<String, Value>, TemplateError> {
+    if expanding
+        .iter()
+        .any(|expanding_name| expanding_name == template_name)
+    {
+        expanding.push(template_name.to_string());
+        return Err(TemplateError::new(
+            key.to_string(),
+            format!(
+                "templates inherit from each other: {}",
+                expanding.join(" -> ")
+            ),
+        ));
+    }
+
+    let mut template = match templates.get(template_name).map(|template| &template.kind) {
+        Some(ValueKind::Table(template)) => template.clone(),
+        Some(_) => {
+            return Err(TemplateError::new(
+                format!("{}.{}", TEMPLATES_KEY, template_name),
+                String::from("a template must be a mapping of server settings"),
+            ))
+        }
+        None => {
+            let mut template_names: Vec<&str> = templates.keys().map(String::as_str).collect();
+            template_names.sort_unstable();
+
+            return Err(TemplateError::new(
+                key.to_string(),
+                format!(
+                    "no template named {} is defined; defined templates are: {}",
+                    template_name,
+                    template_names.join(", ")
+                ),
+            ));
+        }
+    };
+
+    let parent_key = format!("{}.{}.{}", TEMPLATES_KEY, template_name, TEMPLATE_KEY);
+    let parent_name = match template.remove(TEMPLATE_KEY) {
+        Some(parent_name) => template_value_name(&parent_key, parent_name)?,
+        None => return Ok(template),
+    };
+
+    expanding.push(template_name.to_string());
+    let parent = expand_template(templates, &parent_name, &parent_key, expanding)?;
+    expanding.pop();
+
+    Ok(merge_tables(parent, template))
+}
+
+fn template_value_name(key: &str, value: Value) -> Result<String, TemplateError> {
+    match value.kind {
+        ValueKind::String(name) => Ok(name),
+        _ => Err(TemplateError::new(
+            key.to_string(),
+            String::from("must be the name of a template"),
+        )),
+    }
+}
+
+/// Merges `overrides` into `base` key by key. Nested mappings are merged the same way; any other
+/// value, including a list, replaces the base value entirely.
+fn merge_tables(mut base: Map<String, Value>, overrides: Map<String, Value>) -> Map<String, Value> {
+    for (key, value) in overrides {
+        let merged_value = match (base.remove(&key), value) {
+            (
+                Some(Value {
+                    kind: ValueKind::Table(base_table),
+                    ..
+                }),
+                Value {
+                    kind: ValueKind::Table(override_table),
+                    ..
+                },
+            ) => Value::new(
+                None,
+                ValueKind::Table(merge_tables(base_table, override_table)),
+            ),
+            (_, value) => value,
+        };
+
+        base.insert(key, merged_value);
+    }
+
+    base
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, File, FileFormat, Source};
+
+    use super::*;
+
+    fn table(yaml: &str) -> Map<String, Value> {
+        Config::builder()
+            .add_source(File::from_str(yaml, FileFormat::Yaml))
+            .build()
+            .unwrap()
+            .collect()
+            .unwrap()
+    }
+
+    fn value_at<'a>(table: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+        let (key, remaining_path) = match path.split_once('.') {
+            Some((key, remaining_path)) => (key, Some(remaining_path)),
+            None => (path, None),
+        };
+        let value = table.get(key)?;
+
+        match (remaining_path, &value.kind) {
+            (None, _) => Some(value),
+            (Some(remaining_path), ValueKind::Table(child)) => value_at(child, remaining_path),
+            (Some(_), _) => None,
+        }
+    }
+
+    fn string_at(table: &Map<String, Value>, path: &str) -> Option<String> {
+        value_at(table, path).map(|value| value.clone().into_string().unwrap())
+    }
+
+    #[test]
+    fn servers_inherit_from_templates_and_keep_their_own_values() {
+        let mut config = table(
+            "
+templates:
+  base:
+    papermc:
+      project: { name: paper, version: 1.18.1 }
+      jvm_arguments: [-Xmx1G]
+      restart: { policy: on-failure, max_restarts: 3 }
+servers:
+  Lobby:
+    template: base
+    papermc:
+      jvm_arguments: [-Xmx2G, -Xms2G]
+      restart: { max_restarts: 10 }
+",
+        );
+        let sources = resolve_templates(&mut config).unwrap_or_else(|e| panic!("{}", e));
+
+        assert_eq!(
+            string_at(&config, "servers.Lobby.papermc.project.name").as_deref(),
+            Some("paper")
+        );
+        assert_eq!(
+            string_at(&config, "servers.Lobby.papermc.restart.policy").as_deref(),
+            Some("on-failure")
+        );
+        assert_eq!(
+            string_at(&config, "servers.Lobby.papermc.restart.max_restarts").as_deref(),
+            Some("10")
+        );
+        let jvm_arguments = value_at(&config, "servers.Lobby.papermc.jvm_arguments")
+            .unwrap()
+            .clone()
+            .into_array()
+            .unwrap();
+        assert_eq!(jvm_arguments.len(), 2);
+        assert!(value_at(&config, "servers.Lobby.template").is_none());
+        assert_eq!(
+            sources
+                .get("servers.Lobby.papermc.project.name")
+                .map(String::as_str),
+            Some("template base")
+        );
+    }
+
+    #[test]
+    fn templates_inherit_from_other_templates() {
+        let mut config = table(
+            "
+templates:
+  base:
+    vanilla: { version: 1.18.2, jvm_arguments: [-Xmx1G] }
+  large:
+    template: base
+    vanilla: { jvm_arguments: [-Xmx8G] }
+servers:
+  Survival:
+    template: large
+",
+        );
+        resolve_templates(&mut config).unwrap_or_else(|e| panic!("{}", e));
+
+        assert_eq!(
+            string_at(&config, "servers.Survival.vanilla.version").as_deref(),
+            Some("1.18.2")
+        );
+        let jvm_arguments = value_at(&config, "servers.Survival.vanilla.jvm_arguments")
+            .unwrap()
+            .clone()
+            .into_array()
+            .unwrap();
+        assert_eq!(
+            jvm_arguments
+                .into_iter()
+                .map(|argument| argument.into_string().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["-Xmx8G"]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_templates() {
+        let mut config = table(
+            "
+templates:
+  base: { vanilla: { version: 1.18.2 } }
+servers:
+  Survival:
+    template: missing
+",
+        );
+        let error = resolve_templates(&mut config).err().unwrap();
+
+        assert_eq!(error.key, "servers.Survival.template");
+        assert!(error.message.contains("defined templates are: base"));
+    }
+
+    #[test]
+    fn rejects_templates_that_inherit_from_each_other() {
+        let mut config = table(
+            "
+templates:
+  first: { template: second }
+  second: { template: first }
+servers:
+  Survival:
+    template: first
+",
+        );
+        let error = resolve_templates(&mut config).err().unwrap();
+
+        assert!(error.message.contains("first -> second -> first"));
+    }
+}
@@ -0,0 +1,929 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+
+use anyhow::Error;
+use config::{Source, Value, ValueKind};
+use emoji::symbols::other_symbol::CROSS_MARK;
+use emoji::symbols::warning::WARNING;
+use reqwest::Client;
+
+use crate::config::layers::{build_layered_config, ConfigLayer};
+use crate::config::migrate::{CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION};
+use crate::config::templates::resolve_templates;
+use crate::config::{ServerType, Stainless};
+use crate::custom::CustomJar;
+use crate::fabric;
+use crate::fabric::FabricServer;
+use crate::forge;
+use crate::forge::ForgeServer;
+use crate::fork::ForkServer;
+use crate::papermc::{query, PaperMCServer};
+use crate::server::health::HealthCheck;
+use crate::server::restart::{RestartCondition, RestartPolicy};
+use crate::server::schedule;
+use crate::server::updates::BackgroundUpdates;
+use crate::server::{Server, ServerApplication};
+use crate::vanilla;
+use crate::vanilla::VanillaServer;
+
+#[derive(PartialEq)]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    pub key: String,
+    pub location: Option<String>,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn error(key: String, message: String) -> ConfigIssue {
+        ConfigIssue {
+            severity: IssueSeverity::Error,
+            key,
+            location: None,
+            message,
+        }
+    }
+
+    fn warning(key: String, message: String) -> ConfigIssue {
+        ConfigIssue {
+            severity: IssueSeverity::Warning,
+            key,
+            location: None,
+            message,
+        }
+    }
+}
+
+impl Display for ConfigIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let glyph = match self.severity {
+            IssueSeverity::Error => CROSS_MARK.glyph,
+            IssueSeverity::Warning => WARNING.glyph,
+        };
+
+        match &self.location {
+            Some(location) => write!(
+                f,
+                "{} {}: `{}`: {}",
+                glyph, location, self.key, self.message
+            ),
+            None => write!(f, "{} `{}`: {}", glyph, self.key, self.message),
+        }
+    }
+}
+
+pub fn validate_layers(layers: &[ConfigLayer]) -> (Option<Stainless>, Vec<ConfigIssue>) {
+    let merged_config = match build_layered_config(layers) {
+        Ok(config) => config,
+        Err(e) => {
+            return (
+                None,
+                vec![ConfigIssue::error(String::from("<root>"), e.to_string())],
+            )
+        }
+    };
+
+    let mut issues = vec![];
+
+    for layer in layers {
+        if let Some(original_version) = layer.migrated_from {
+            issues.push(ConfigIssue::warning(
+                String::from(CONFIG_VERSION_KEY),
+                format!(
+                    "{} uses config_version {} and was upgraded to {} in memory; run `stainless config migrate --write` to upgrade the file",
+                    layer.name, original_version, CURRENT_CONFIG_VERSION
+                ),
+            ));
+        }
+    }
+
+    if let Ok(version) = merged_config.get::<u32>(CONFIG_VERSION_KEY) {
+        if version > CURRENT_CONFIG_VERSION {
+            issues.push(ConfigIssue::warning(
+                String::from(CONFIG_VERSION_KEY),
+                format!(
+                    "config_version {} is newer than the newest version this Stainless understands ({}); upgrade Stainless to use this configuration",
+                    version, CURRENT_CONFIG_VERSION
+                ),
+            ));
+        }
+    }
+
+    let mut merged_table = match merged_config.collect() {
+        Ok(table) => table,
+        Err(e) => {
+            issues.push(ConfigIssue::error(String::from("<root>"), e.to_string()));
+            return (None, issues);
+        }
+    };
+
+    if let Err(e) = resolve_templates(&mut merged_table) {
+        issues.push(ConfigIssue::error(e.key, e.message));
+        locate_issues(layers, &mut issues);
+        return (None, issues);
+    }
+
+    let merged_value = Value::new(None, ValueKind::Table(merged_table));
+    let config = match serde_path_to_error::deserialize::<_, Stainless>(merged_value) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            let key = e.path().to_string();
+            issues.push(ConfigIssue::error(key, e.into_inner().to_string()));
+            None
+        }
+    };
+
+    if let Some(config) = &config {
+        if config.servers.is_empty() {
+            issues.push(ConfigIssue::error(
+                String::from("servers"),
+                String::from("no servers are defined"),
+            ));
+        }
+
+        for (name, server_type) in &config.servers {
+            let key = format!("servers.{}.{}", name, server_type.backend_name());
+
+            issues.extend(match server_type {
+                ServerType::PaperMC(server) => check_server(&key, server),
+                ServerType::Vanilla(server) => check_server(&key, server),
+                ServerType::Fabric(server) => check_server(&key, server),
+                ServerType::Forge(server) => check_server(&key, server),
+                ServerType::Fork(server) => check_server(&key, server),
+                ServerType::Custom(server) => {
+                    let mut server_issues = check_server(&key, server);
+                    server_issues.extend(check_custom_jar(&format!("{}.jar", key), &server.jar));
+                    server_issues
+                }
+            });
+        }
+    }
+
+    locate_issues(layers, &mut issues);
+
+    (config, issues)
+}
+
+pub async fn validate_projects_online(
+    layers: &[ConfigLayer],
+    config: &Stainless,
+    http_client: &Client,
+) -> Vec<ConfigIssue> {
+    let mut papermc_servers = vec![];
+    let mut vanilla_servers = vec![];
+    let mut fabric_servers = vec![];
+    let mut forge_servers = vec![];
+    let mut fork_servers = vec![];
+    for (name, server_type) in &config.servers {
+        match server_type {
+            ServerType::PaperMC(server) => papermc_servers.push((name, server)),
+            ServerType::Vanilla(server) => vanilla_servers.push((name, server)),
+            ServerType::Fabric(server) => fabric_servers.push((name, server)),
+            ServerType::Forge(server) => forge_servers.push((name, server)),
+            ServerType::Fork(server) => fork_servers.push((name, server)),
+            // Custom jars come from no API, so there is nothing to check online.
+            ServerType::Custom(_) => {}
+        }
+    }
+
+    let mut issues = vec![];
+    if !papermc_servers.is_empty() {
+        issues.extend(check_papermc_projects_online(&papermc_servers, http_client).await);
+    }
+    if !vanilla_servers.is_empty() {
+        issues.extend(check_vanilla_versions_online(&vanilla_servers, http_client).await);
+    }
+    if !fabric_servers.is_empty() {
+        issues.extend(check_fabric_versions_online(&fabric_servers, http_client).await);
+    }
+    if !forge_servers.is_empty() {
+        issues.extend(check_forge_versions_online(&forge_servers, http_client).await);
+    }
+    if !fork_servers.is_empty() {
+        issues.extend(check_fork_versions_online(&fork_servers, http_client).await);
+    }
+
+    locate_issues(layers, &mut issues);
+
+    issues
+}
+
+async fn check_papermc_projects_online(
+    servers: &[(&String, &PaperMCServer)],
+    http_client: &Client,
+) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+    let known_projects = match query::papermc_projects(http_client).await {
+        Ok(projects) => projects,
+        Err(e) => {
+            issues.push(ConfigIssue::warning(
+                String::from("servers"),
+                format!(
+                    "could not reach the PaperMC API, so projects were not checked: {}",
+                    e
+                ),
+            ));
+            return issues;
+        }
+    };
+
+    for (name, server) in servers {
+        let project_key = format!("servers.{}.papermc.project", name);
+
+        if !known_projects.contains(&server.project.name) {
+            issues.push(ConfigIssue::error(
+                format!("{}.name", project_key),
+                format!(
+                    "PaperMC has no project named {}; available projects are: {}",
+                    server.project.name,
+                    known_projects.join(", ")
+                ),
+            ));
+            continue;
+        }
+
+        match query::papermc_project_versions(&server.project.name, http_client).await {
+            Ok(versions) if !versions.contains(&server.project.version) => {
+                issues.push(ConfigIssue::error(
+                    format!("{}.version", project_key),
+                    format!(
+                        "{} has no version {}; available versions are: {}",
+                        server.project.name,
+                        server.project.version,
+                        versions.join(", ")
+                    ),
+                ))
+            }
+            Ok(_) => {}
+            Err(e) => issues.push(ConfigIssue::warning(
+                format!("{}.version", project_key),
+                format!("could not check version against the PaperMC API: {}", e),
+            )),
+        }
+    }
+
+    issues
+}
+
+async fn check_vanilla_versions_online(
+    servers: &[(&String, &VanillaServer)],
+    http_client: &Client,
+) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+    let known_versions = match vanilla::query::vanilla_versions(http_client).await {
+        Ok(versions) => versions,
+        Err(e) => {
+            issues.push(ConfigIssue::warning(
+                String::from("servers"),
+                format!(
+                    "could not reach Mojang's version manifest, so vanilla versions were not checked: {}",
+                    e
+                ),
+            ));
+            return issues;
+        }
+    };
+
+    for (name, server) in servers {
+        let version = server.version.as_str();
+        if version != vanilla::LATEST_RELEASE
+            && version != vanilla::LATEST_SNAPSHOT
+            && !known_versions.iter().any(|known| known == version)
+        {
+            issues.push(ConfigIssue::error(
+                format!("servers.{}.vanilla.version", name),
+                format!(
+                    "Minecraft has no version {}; use a version such as 1.18.2, or {} or {}",
+                    version,
+                    vanilla::LATEST_RELEASE,
+                    vanilla::LATEST_SNAPSHOT
+                ),
+            ));
+        }
+    }
+
+    issues
+}
+
+pub fn report_issues(issues: &[ConfigIssue]) -> crate::Result<()> {
+    for issue in issues {
+        println!("{}", issue);
+    }
+
+    let error_count = issues
+        .iter()
+        .filter(|issue| issue.severity == IssueSeverity::Error)
+        .count();
+
+    if error_count > 0 {
+        return Err(Error::msg(format!(
+            "configuration has {} error(s)",
+            error_count
+        )));
+    }
+
+    Ok(())
+}
+
+async fn check_fabric_versions_online(
+    servers: &[(&String, &FabricServer)],
+    http_client: &Client,
+) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+    let known_versions = match fabric::query::fabric_game_versions(http_client).await {
+        Ok(versions) => versions,
+        Err(e) => {
+            issues.push(ConfigIssue::warning(
+                String::from("servers"),
+                format!(
+                    "could not reach the Fabric meta API, so Fabric versions were not checked: {}",
+                    e
+                ),
+            ));
+            return issues;
+        }
+    };
+
+    for (name, server) in servers {
+        let key = format!("servers.{}.fabric", name);
+        let minecraft_version = server.minecraft_version.as_str();
+
+        if minecraft_version != vanilla::LATEST_RELEASE
+            && minecraft_version != vanilla::LATEST_SNAPSHOT
+            && !known_versions
+                .iter()
+                .any(|known| known == minecraft_version)
+        {
+            issues.push(ConfigIssue::error(
+                format!("{}.minecraft_version", key),
+                format!("Fabric does not support Minecraft {}", minecraft_version),
+            ));
+            continue;
+        }
+
+        let loader_version = match &server.loader_version {
+            Some(loader_version) if !minecraft_version.starts_with("latest.") => loader_version,
+            _ => continue,
+        };
+        match fabric::query::fabric_loader_versions(minecraft_version, http_client).await {
+            Ok(loader_versions) if !loader_versions.contains(loader_version) => {
+                issues.push(ConfigIssue::error(
+                    format!("{}.loader_version", key),
+                    format!(
+                        "Fabric loader {} is not available for Minecraft {}",
+                        loader_version, minecraft_version
+                    ),
+                ))
+            }
+            Ok(_) => {}
+            Err(e) => issues.push(ConfigIssue::warning(
+                format!("{}.loader_version", key),
+                format!("could not check loader against the Fabric meta API: {}", e),
+            )),
+        }
+    }
+
+    issues
+}
+
+async fn check_forge_versions_online(
+    servers: &[(&String, &ForgeServer)],
+    http_client: &Client,
+) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+
+    for (name, server) in servers {
+        let key = format!("servers.{}.forge", name);
+
+        match forge::query::forge_versions(server.loader, &server.minecraft_version, http_client)
+            .await
+        {
+            Ok(versions) if versions.is_empty() => issues.push(ConfigIssue::error(
+                format!("{}.minecraft_version", key),
+                format!(
+                    "{} has no versions for Minecraft {}",
+                    server.loader, server.minecraft_version
+                ),
+            )),
+            Ok(versions) => {
+                if let Some(version) = &server.version {
+                    if !versions.contains(version) {
+                        issues.push(ConfigIssue::error(
+                            format!("{}.version", key),
+                            format!(
+                                "{} {} is not available for Minecraft {}",
+                                server.loader, version, server.minecraft_version
+                            ),
+                        ));
+                    }
+                }
+            }
+            Err(e) => issues.push(ConfigIssue::warning(
+                format!("{}.version", key),
+                format!(
+                    "could not check versions against the {} Maven repository: {}",
+                    server.loader, e
+                ),
+            )),
+        }
+    }
+
+    issues
+}
+
+async fn check_fork_versions_online(
+    servers: &[(&String, &ForkServer)],
+    http_client: &Client,
+) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+
+    for (name, server) in servers {
+        let key = format!("servers.{}.fork.version", name);
+        let distributor = server.project.distributor();
+
+        match distributor.versions(http_client).await {
+            Ok(versions) if !versions.contains(&server.version) => issues.push(ConfigIssue::error(
+                key,
+                format!(
+                    "{} has no version {}; available versions are: {}",
+                    distributor.name(),
+                    server.version,
+                    versions.join(", ")
+                ),
+            )),
+            Ok(_) => {}
+            Err(e) => issues.push(ConfigIssue::warning(
+                key,
+                format!(
+                    "could not check version against the {} API: {}",
+                    distributor.name(),
+                    e
+                ),
+            )),
+        }
+    }
+
+    issues
+}
+
+fn check_server<S: Server<S, A>, A: ServerApplication<S, A>>(
+    key: &str,
+    server: &S,
+) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+
+    issues.extend(check_jvm_arguments(
+        &format!("{}.jvm_arguments", key),
+        server.jvm_arguments(),
+    ));
+    issues.extend(check_restart_policy(
+        &format!("{}.restart", key),
+        server.restart_policy(),
+    ));
+    if server.hooks().timeout_seconds == 0 {
+        issues.push(ConfigIssue::warning(
+            format!("{}.hooks.timeout_seconds", key),
+            String::from("is 0, so every hook command times out immediately"),
+        ));
+    }
+    if let Some(background_updates) = server.background_updates() {
+        issues.extend(check_background_updates(
+            &format!("{}.updates", key),
+            background_updates,
+        ));
+    }
+    if let Some(health_check) = server.health_check() {
+        issues.extend(check_health_check(
+            &format!("{}.health_check", key),
+            health_check,
+        ));
+    }
+
+    issues
+}
+
+fn check_custom_jar(key: &str, jar: &CustomJar) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+
+    match (&jar.path, &jar.url) {
+        (Some(_), Some(_)) => issues.push(ConfigIssue::error(
+            key.to_string(),
+            String::from("sets both `path` and `url`; a jar comes from only one of them"),
+        )),
+        (None, None) => issues.push(ConfigIssue::error(
+            key.to_string(),
+            String::from("needs either `path` to a local jar or `url` to download one from"),
+        )),
+        (Some(_), None) => {}
+        (None, Some(url)) => {
+            if let Err(e) = reqwest::Url::parse(url) {
+                issues.push(ConfigIssue::error(
+                    format!("{}.url", key),
+                    format!("\"{}\" is not a valid URL: {}", url, e),
+                ));
+            }
+            if jar.sha256.is_none() {
+                issues.push(ConfigIssue::error(
+                    format!("{}.sha256", key),
+                    String::from("is required with `url`, so that the download can be verified"),
+                ));
+            }
+        }
+    }
+
+    if let Some(sha256) = &jar.sha256 {
+        if !matches!(hex::decode(sha256), Ok(hash) if hash.len() == 32) {
+            issues.push(ConfigIssue::error(
+                format!("{}.sha256", key),
+                format!("\"{}\" is not a SHA-256 hash of 64 hex digits", sha256),
+            ));
+        }
+    }
+
+    issues
+}
+
+fn check_jvm_arguments(key: &str, jvm_arguments: &[String]) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+    let mut initial_heap = None;
+    let mut maximum_heap = None;
+
+    for argument in jvm_arguments {
+        if !argument.starts_with('-') {
+            issues.push(ConfigIssue::warning(
+                key.to_string(),
+                format!(
+                    "\"{}\" does not look like a JVM option; options start with '-'",
+                    argument
+                ),
+            ));
+        } else if argument == "-jar" {
+            issues.push(ConfigIssue::error(
+                key.to_string(),
+                String::from("-jar is added by Stainless and must not be configured"),
+            ));
+        } else if let Some(size) = argument.strip_prefix("-Xms") {
+            initial_heap = check_memory_size(key, argument, size, initial_heap, &mut issues);
+        } else if let Some(size) = argument.strip_prefix("-Xmx") {
+            maximum_heap = check_memory_size(key, argument, size, maximum_heap, &mut issues);
+        }
+    }
+
+    if let (Some(initial_heap), Some(maximum_heap)) = (initial_heap, maximum_heap) {
+        if maximum_heap < initial_heap {
+            issues.push(ConfigIssue::error(
+                key.to_string(),
+                String::from(
+                    "the maximum heap size (-Xmx) is lower than the initial heap size (-Xms)",
+                ),
+            ));
+        }
+    }
+
+    issues
+}
+
+fn check_restart_policy(key: &str, restart: &RestartPolicy) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+
+    if restart.initial_backoff_seconds > restart.max_backoff_seconds {
+        issues.push(ConfigIssue::warning(
+            format!("{}.initial_backoff_seconds", key),
+            String::from(
+                "is longer than max_backoff_seconds, so every delay is max_backoff_seconds",
+            ),
+        ));
+    }
+
+    if restart.max_restarts == 0 && restart.policy != RestartCondition::Never {
+        issues.push(ConfigIssue::warning(
+            format!("{}.max_restarts", key),
+            String::from("is 0, so Stainless gives up on the server the first time it exits"),
+        ));
+    }
+
+    if let Some(restart_schedule) = &restart.schedule {
+        let schedule_key = format!("{}.schedule", key);
+
+        if let Err(e) = schedule::parse_cron(&restart_schedule.cron) {
+            issues.push(ConfigIssue::error(
+                format!("{}.cron", schedule_key),
+                e.to_string(),
+            ));
+        }
+        if let Some(time_zone) = &restart_schedule.time_zone {
+            if let Err(e) = schedule::parse_time_zone(time_zone) {
+                issues.push(ConfigIssue::error(
+                    format!("{}.time_zone", schedule_key),
+                    e.to_string(),
+                ));
+            }
+        }
+        if let Err(e) = schedule::parse_warnings(&restart_schedule.warnings) {
+            issues.push(ConfigIssue::error(
+                format!("{}.warnings", schedule_key),
+                e.to_string(),
+            ));
+        }
+    }
+
+    issues
+}
+
+fn check_background_updates(key: &str, background_updates: &BackgroundUpdates) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+
+    if background_updates.check_interval_minutes == 0 {
+        issues.push(ConfigIssue::error(
+            format!("{}.check_interval_minutes", key),
+            String::from("must be at least 1"),
+        ));
+    }
+
+    if let Err(e) = schedule::parse_warnings(&background_updates.restart_warnings) {
+        issues.push(ConfigIssue::error(
+            format!("{}.restart_warnings", key),
+            e.to_string(),
+        ));
+    }
+
+    issues
+}
+
+fn check_health_check(key: &str, health_check: &HealthCheck) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+
+    if health_check.interval_seconds == 0 {
+        issues.push(ConfigIssue::error(
+            format!("{}.interval_seconds", key),
+            String::from("must be at least 1"),
+        ));
+    }
+
+    if health_check.failure_threshold == 0 {
+        issues.push(ConfigIssue::error(
+            format!("{}.failure_threshold", key),
+            String::from("must be at least 1"),
+        ));
+    }
+
+    if health_check.timeout_seconds >= health_check.interval_seconds {
+        issues.push(ConfigIssue::warning(
+            format!("{}.timeout_seconds", key),
+            String::from("is not shorter than interval_seconds, so slow checks delay the next one"),
+        ));
+    }
+
+    issues
+}
+
+fn check_memory_size(
+    key: &str,
+    argument: &str,
+    size: &str,
+    previous_size: Option<u64>,
+    issues: &mut Vec<ConfigIssue>,
+) -> Option<u64> {
+    if previous_size.is_some() {
+        issues.push(ConfigIssue::warning(
+            key.to_string(),
+            format!("{} overrides an earlier value of the same option", argument),
+        ));
+    }
+
+    match parse_memory_size(size) {
+        Some(bytes) => {
+            if bytes < 1024 * 1024 {
+                issues.push(ConfigIssue::warning(
+                    key.to_string(),
+                    format!(
+                        "{} is less than one megabyte; did you forget a unit such as M or G?",
+                        argument
+                    ),
+                ));
+            }
+            Some(bytes)
+        }
+        None => {
+            issues.push(ConfigIssue::error(
+                key.to_string(),
+                format!(
+                    "{} is not a valid memory size; expected a number optionally followed by K, M, G or T",
+                    argument
+                ),
+            ));
+            previous_size
+        }
+    }
+}
+
+fn parse_memory_size(size: &str) -> Option<u64> {
+    let (number, multiplier) = match size.chars().last()?.to_ascii_uppercase() {
+        'K' => (&size[..size.len() - 1], 1 << 10),
+        'M' => (&size[..size.len() - 1], 1 << 20),
+        'G' => (&size[..size.len() - 1], 1 << 30),
+        'T' => (&size[..size.len() - 1], 1 << 40),
+        _ => (size, 1),
+    };
+
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+fn locate_issues(layers: &[ConfigLayer], issues: &mut [ConfigIssue]) {
+    for issue in issues.iter_mut() {
+        issue.location = locate_key(layers, &issue.key, unknown_field(&issue.message));
+    }
+}
+
+/// Name of the field an unknown field error is about. Such errors are reported at the struct
+/// holding the field, or above it when the struct is flattened into another.
+fn unknown_field(message: &str) -> Option<&str> {
+    let field = message.strip_prefix("unknown field `")?;
+
+    field.split_once('`').map(|(field, _)| field)
+}
+
+fn locate_key(layers: &[ConfigLayer], key: &str, unknown_field: Option<&str>) -> Option<String> {
+    let key_path: Vec<&str> = key.split('.').collect();
+    let full_depth = key_path.len() + unknown_field.map_or(0, |_| 1);
+    let mut best_match: Option<(usize, String)> = None;
+
+    for layer in layers.iter().rev() {
+        let (matched_depth, location) = match &layer.file {
+            Some(file) => {
+                let contents = match fs::read_to_string(file) {
+                    Ok(contents) => contents,
+                    Err(_) => continue,
+                };
+
+                match find_key_line(&contents, &key_path, unknown_field) {
+                    Some((matched_depth, line)) => {
+                        (matched_depth, format!("{}:{}", file.display(), line))
+                    }
+                    None => continue,
+                }
+            }
+            None if layer.defines_key(&key_path) => (key_path.len(), layer.name.clone()),
+            None => continue,
+        };
+
+        if matched_depth == full_depth {
+            return Some(location);
+        }
+
+        match &best_match {
+            Some((best_depth, _)) if *best_depth >= matched_depth => {}
+            _ => best_match = Some((matched_depth, location)),
+        }
+    }
+
+    best_match.map(|(_, location)| location)
+}
+
+/// Finds the line of the deepest key of `key_path` present in a YAML document by following
+/// indentation. Returns how many keys of the path matched along with the 1-based line number.
+/// With `descendant`, a key of that name anywhere below the path counts as one more matched key.
+fn find_key_line(
+    contents: &str,
+    key_path: &[&str],
+    descendant: Option<&str>,
+) -> Option<(usize, usize)> {
+    let mut open_keys: Vec<(usize, String)> = vec![];
+    let mut best_match = None;
+
+    for (line_index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('-') {
+            continue;
+        }
+
+        let key = match trimmed.split_once(':') {
+            Some((key, _)) => key.trim().trim_matches(|c| c == '"' || c == '\''),
+            None => continue,
+        };
+        let indent = line.len() - trimmed.len();
+
+        while matches!(open_keys.last(), Some((open_indent, _)) if *open_indent >= indent) {
+            open_keys.pop();
+        }
+        open_keys.push((indent, key.to_string()));
+
+        // Keys below the path only count when they are the descendant searched for.
+        let is_on_path = open_keys
+            .iter()
+            .zip(key_path)
+            .all(|((_, open_key), path_key)| open_key == path_key);
+        let depth = match open_keys.len() {
+            depth if depth <= key_path.len() => depth,
+            _ if descendant == Some(key) => key_path.len() + 1,
+            _ => continue,
+        };
+
+        if is_on_path {
+            match best_match {
+                Some((best_depth, _)) if best_depth >= depth => {}
+                _ => best_match = Some((depth, line_index + 1)),
+            }
+        }
+    }
+
+    best_match
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jvm_argument_errors(jvm_arguments: &[&str]) -> Vec<String> {
+        let jvm_arguments: Vec<String> = jvm_arguments
+            .iter()
+            .map(|argument| argument.to_string())
+            .collect();
+
+        check_jvm_arguments("jvm_arguments", &jvm_arguments)
+            .into_iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    #[test]
+    fn rejects_a_maximum_heap_below_the_initial_heap() {
+        assert_eq!(
+            jvm_argument_errors(&["-Xms4G", "-Xmx2G"]),
+            vec!["the maximum heap size (-Xmx) is lower than the initial heap size (-Xms)"]
+        );
+        assert!(jvm_argument_errors(&["-Xms2G", "-Xmx4096M"]).is_empty());
+    }
+
+    #[test]
+    fn parses_memory_sizes_with_units() {
+        assert_eq!(parse_memory_size("512"), Some(512));
+        assert_eq!(parse_memory_size("64k"), Some(64 << 10));
+        assert_eq!(parse_memory_size("512M"), Some(512 << 20));
+        assert_eq!(parse_memory_size("2g"), Some(2 << 30));
+    }
+
+    #[test]
+    fn rejects_memory_sizes_with_unknown_units() {
+        assert_eq!(parse_memory_size("2GB"), None);
+        assert_eq!(parse_memory_size("2X"), None);
+        assert_eq!(parse_memory_size("G"), None);
+        assert_eq!(parse_memory_size(""), None);
+    }
+
+    static CONFIG: &str = "\
+servers:
+  Lobby:
+    papermc:
+      # Comments are skipped.
+      jvm_arguments: [ \"-Xmx2G\" ]
+      restart:
+        polcy: never
+  Arena:
+    vanilla:
+      version: \"1.18.2\"
+";
+
+    #[test]
+    fn finds_the_line_of_a_key() {
+        assert_eq!(
+            find_key_line(CONFIG, &["servers", "Arena", "vanilla", "version"], None),
+            Some((4, 10))
+        );
+    }
+
+    #[test]
+    fn finds_the_deepest_line_of_a_key_that_is_not_set() {
+        assert_eq!(
+            find_key_line(CONFIG, &["servers", "Lobby", "papermc", "hooks"], None),
+            Some((3, 3))
+        );
+    }
+
+    #[test]
+    fn finds_a_field_below_the_key_it_is_reported_at() {
+        assert_eq!(
+            find_key_line(CONFIG, &["servers", "Lobby", "papermc"], Some("polcy")),
+            Some((4, 7))
+        );
+        assert_eq!(
+            find_key_line(CONFIG, &["servers", "Arena", "vanilla"], Some("polcy")),
+            Some((3, 9))
+        );
+    }
+
+    #[test]
+    fn reads_the_field_of_unknown_field_errors() {
+        assert_eq!(
+            unknown_field("unknown field `polcy`, expected one of `policy`"),
+            Some("polcy")
+        );
+        assert_eq!(unknown_field("invalid type: string \"4\""), None);
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt::{Display, Formatter};
+use std::fs::{self, remove_file, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use anyhow::Error;
+use async_trait::async_trait;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use reqwest::{Client, Url};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+
+use crate::server::download::download_verified_file;
+use crate::server::process::{run_server_process, ProcessChannels};
+use crate::server::{Server, ServerApplication, ServerSettings};
+
+static DEFAULT_JAR_NAME: &str = "server.jar";
+
+/// Where a custom server's jar comes from: either `path`, or `url` along with `sha256`.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomJar {
+    /// Local jar to run. Relative paths start from the Stainless home directory. The jar is
+    /// hashed whenever the server is checked for updates, so replacing it updates the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// URL to download the jar from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Expected SHA-256 of the jar, in hex. Required with `url`; with `path`, a local jar with any
+    /// other hash is refused. Changing it updates the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(skip)]
+    pub resolved_path: Option<PathBuf>,
+}
+
+impl CustomJar {
+    /// Describes the jar's source, such as its path or URL.
+    fn source(&self) -> crate::Result<String> {
+        match (&self.resolved_path, &self.url) {
+            (Some(path), None) => Ok(path.display().to_string()),
+            (None, Some(url)) => Ok(url.clone()),
+            _ => Err(Error::msg("a custom jar needs either `path` or `url`")),
+        }
+    }
+
+    /// File name the jar is stored under in the working directory.
+    fn file_name(&self) -> crate::Result<String> {
+        let file_name = match (&self.resolved_path, &self.url) {
+            (Some(path), None) => path
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().to_string()),
+            (None, Some(url)) => Url::parse(url)?
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| segment.to_string()),
+            _ => return Err(Error::msg("a custom jar needs either `path` or `url`")),
+        };
+
+        Ok(file_name.unwrap_or_else(|| String::from(DEFAULT_JAR_NAME)))
+    }
+
+    /// SHA-256 the jar is expected to have: the hash of the local jar, which has to match the
+    /// configured one if there is one, or the configured one.
+    fn sha256(&self) -> crate::Result<Vec<u8>> {
+        match (&self.resolved_path, &self.sha256) {
+            (Some(path), None) => hash_file(path),
+            (Some(path), Some(sha256)) => {
+                let local_sha256 = hash_file(path)?;
+
+                if local_sha256 != hex::decode(sha256)? {
+                    return Err(Error::msg(format!(
+                        "jar {} has SHA-256 {}, which does not match the configured sha256 {}",
+                        path.display(),
+                        hex::encode(&local_sha256),
+                        sha256
+                    )));
+                }
+
+                Ok(local_sha256)
+            }
+            (None, Some(sha256)) => Ok(hex::decode(sha256)?),
+            (None, None) => Err(Error::msg(
+                "a custom jar downloaded from `url` needs `sha256`",
+            )),
+        }
+    }
+}
+
+/// A server running a jar Stainless does not know how to update, from a local path or a URL.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomServer {
+    /// Jar the server runs, copied into the working directory from a local path or downloaded
+    /// from a URL.
+    pub jar: CustomJar,
+    #[serde(flatten)]
+    pub settings: ServerSettings,
+}
+
+impl Server<CustomServer, CustomServerApp> for CustomServer {
+    fn settings(&self) -> &ServerSettings {
+        &self.settings
+    }
+
+    fn default_version_check_client(&self) -> CustomServerApp {
+        CustomServerApp::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct CustomServerApp {
+    /// File name of the jar in the working directory.
+    pub jar_name: String,
+    /// Path or URL the jar came from.
+    pub source: String,
+    pub sha256: Vec<u8>,
+}
+
+impl Display for CustomServerApp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{Name: {}, Source: {}, SHA256: {}}}",
+            self.jar_name,
+            self.source,
+            hex::encode(&self.sha256)
+        )
+    }
+}
+
+#[async_trait]
+impl ServerApplication<CustomServer, CustomServerApp> for CustomServerApp {
+    fn application_name(&self) -> &str {
+        &self.jar_name
+    }
+
+    fn build_name(&self) -> String {
+        let sha256 = hex::encode(&self.sha256);
+
+        format!("custom-{}", &sha256[..sha256.len().min(12)])
+    }
+
+    fn matches_config(&self, config: &CustomServer) -> bool {
+        // A local jar without a configured hash is only known to have changed once it is hashed.
+        let configured_sha256 = config.jar.sha256.as_ref().map(hex::decode);
+
+        config.jar.source().ok().as_ref() == Some(&self.source)
+            && configured_sha256
+                .iter()
+                .all(|sha256| sha256.as_ref().ok() == Some(&self.sha256))
+    }
+
+    async fn check_for_updated_server(
+        &self,
+        config: &CustomServer,
+        _http_client: &Client,
+    ) -> crate::Result<Option<CustomServerApp>> {
+        let sha256 = config.jar.sha256()?;
+
+        // Nothing publishes newer builds of a custom jar, so the only update is a changed jar.
+        if sha256 != self.sha256 {
+            if !self.sha256.is_empty() {
+                println!(
+                    "{} The jar of {} changed from SHA256 {} to {}",
+                    INFORMATION.glyph,
+                    config.server_name(),
+                    hex::encode(&self.sha256),
+                    hex::encode(&sha256)
+                );
+            }
+            Ok(Some(CustomServerApp {
+                jar_name: config.jar.file_name()?,
+                source: config.jar.source()?,
+                sha256,
+            }))
+        } else {
+            println!("{} The jar is unchanged!", CHECK_MARK.glyph);
+            Ok(None)
+        }
+    }
+
+    async fn download_server(
+        &self,
+        config: &CustomServer,
+        directory: &Path,
+        http_client: &Client,
+    ) -> crate::Result<()> {
+        let jar_path = directory.join(self.application_name());
+
+        match (&config.jar.resolved_path, &config.jar.url) {
+            (Some(path), None) => {
+                println!(
+                    "{} Copying {} from {}...",
+                    INFORMATION.glyph,
+                    self.application_name(),
+                    path.display()
+                );
+
+                // A jar kept in the working directory is already in place.
+                if !jar_path.exists() || fs::canonicalize(path)? != fs::canonicalize(&jar_path)? {
+                    fs::copy(path, &jar_path)?;
+                }
+                if hash_file(&jar_path)? != self.sha256 {
+                    return Err(Error::msg(format!(
+                        "{} changed while it was copied",
+                        path.display()
+                    )));
+                }
+
+                println!("{} Copied {}!", CHECK_MARK.glyph, self.application_name());
+                Ok(())
+            }
+            (None, Some(url)) => {
+                println!(
+                    "{} Downloading {}...",
+                    INFORMATION.glyph,
+                    self.application_name()
+                );
+
+                download_verified_file::<Sha256>(url, &jar_path, &self.sha256, http_client).await
+            }
+            _ => Err(Error::msg("a custom jar needs either `path` or `url`")),
+        }
+    }
+
+    fn delete_server(
+        &self,
+        config: &CustomServer,
+        _replacement: Option<&CustomServerApp>,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Removing {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        remove_file(config.working_directory().join(self.application_name()))?;
+
+        Ok(())
+    }
+
+    async fn start_server(
+        &self,
+        server_config: &CustomServer,
+        channels: ProcessChannels<'_>,
+    ) -> crate::Result<ExitStatus> {
+        println!(
+            "{} Starting {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        let mut command = Command::new("java");
+        command
+            .args(server_config.jvm_arguments())
+            .arg("-jar")
+            .arg(self.application_name())
+            .arg("nogui")
+            .current_dir(server_config.working_directory());
+
+        run_server_process(self, server_config, command, channels).await
+    }
+}
+
+fn hash_file(path: &Path) -> crate::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+
+    Ok(hasher.finalize().to_vec())
+}
@@ -0,0 +1,160 @@
+use std::fmt::{Display, Formatter};
+use std::fs::remove_file;
+use std::path::Path;
+use std::process::ExitStatus;
+
+use async_trait::async_trait;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::server::process::{run_server_process, ProcessChannels};
+use crate::server::{Server, ServerApplication, ServerSettings};
+use crate::vanilla::{LATEST_RELEASE, LATEST_SNAPSHOT};
+
+pub mod query;
+
+/// A server running the Fabric loader on top of a Minecraft version.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FabricServer {
+    /// Minecraft version to run, such as `1.18.2`, or `latest.release` or `latest.snapshot` to
+    /// follow the newest version Fabric supports.
+    pub minecraft_version: String,
+    /// Fabric loader version to run, such as `0.13.3`. Defaults to the newest stable loader for
+    /// the Minecraft version, which the server is updated to as new loaders are released.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loader_version: Option<String>,
+    #[serde(flatten)]
+    pub settings: ServerSettings,
+}
+
+impl Server<FabricServer, FabricServerApp> for FabricServer {
+    fn settings(&self) -> &ServerSettings {
+        &self.settings
+    }
+
+    fn default_version_check_client(&self) -> FabricServerApp {
+        FabricServerApp::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct FabricServerApp {
+    pub minecraft_version: String,
+    pub loader_version: String,
+    pub installer_version: String,
+    /// File name of the server launcher, which downloads the Minecraft server and the loader's
+    /// libraries when it first starts.
+    pub launcher_name: String,
+}
+
+impl Display for FabricServerApp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{Minecraft: {}, Loader: {}, Installer: {}, Launcher: {}}}",
+            self.minecraft_version, self.loader_version, self.installer_version, self.launcher_name
+        )
+    }
+}
+
+#[async_trait]
+impl ServerApplication<FabricServer, FabricServerApp> for FabricServerApp {
+    fn application_name(&self) -> &str {
+        &self.launcher_name
+    }
+
+    fn build_name(&self) -> String {
+        format!("fabric-{}-{}", self.minecraft_version, self.loader_version)
+    }
+
+    fn matches_config(&self, config: &FabricServer) -> bool {
+        (config.minecraft_version == LATEST_RELEASE
+            || config.minecraft_version == LATEST_SNAPSHOT
+            || config.minecraft_version == self.minecraft_version)
+            && config
+                .loader_version
+                .iter()
+                .all(|loader_version| *loader_version == self.loader_version)
+    }
+
+    async fn check_for_updated_server(
+        &self,
+        config: &FabricServer,
+        http_client: &Client,
+    ) -> crate::Result<Option<FabricServerApp>> {
+        let latest_client = query::latest_fabric_server(config, http_client).await?;
+
+        // The installer only builds the launcher, so a newer one alone is not worth a restart.
+        if latest_client.minecraft_version != self.minecraft_version
+            || latest_client.loader_version != self.loader_version
+        {
+            println!(
+                "{} Fabric loader {} for Minecraft {} is available",
+                CHECK_MARK.glyph, latest_client.loader_version, latest_client.minecraft_version
+            );
+            Ok(Some(latest_client))
+        } else {
+            println!("{} No newer server is available!", CHECK_MARK.glyph);
+            Ok(None)
+        }
+    }
+
+    async fn download_server(
+        &self,
+        _config: &FabricServer,
+        directory: &Path,
+        http_client: &Client,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Downloading {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        query::download_server_launcher(self, &directory.join(self.application_name()), http_client)
+            .await
+    }
+
+    fn delete_server(
+        &self,
+        config: &FabricServer,
+        _replacement: Option<&FabricServerApp>,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Removing {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        remove_file(config.working_directory().join(self.application_name()))?;
+
+        Ok(())
+    }
+
+    async fn start_server(
+        &self,
+        server_config: &FabricServer,
+        channels: ProcessChannels<'_>,
+    ) -> crate::Result<ExitStatus> {
+        println!(
+            "{} Starting {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        let mut command = Command::new("java");
+        command
+            .args(server_config.jvm_arguments())
+            .arg("-jar")
+            .arg(self.application_name())
+            .arg("nogui")
+            .current_dir(server_config.working_directory());
+
+        run_server_process(self, server_config, command, channels).await
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use anyhow::Error;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+
+use crate::fabric::query::response_schema::{GameVersion, InstallerVersion, LoaderVersionsEntry};
+use crate::fabric::{FabricServer, FabricServerApp};
+use crate::server::download::download_file;
+use crate::vanilla::{LATEST_RELEASE, LATEST_SNAPSHOT};
+
+mod response_schema;
+mod url;
+
+pub async fn fabric_game_versions(http_client: &Client) -> crate::Result<Vec<String>> {
+    Ok(
+        call_fabric_meta_api::<Vec<GameVersion>>(&url::fabric_game_versions_url(), http_client)
+            .await?
+            .into_iter()
+            .map(|game_version| game_version.version)
+            .collect(),
+    )
+}
+
+pub async fn fabric_loader_versions(
+    minecraft_version: &str,
+    http_client: &Client,
+) -> crate::Result<Vec<String>> {
+    Ok(call_fabric_meta_api::<Vec<LoaderVersionsEntry>>(
+        &url::fabric_loader_versions_url(minecraft_version),
+        http_client,
+    )
+    .await?
+    .into_iter()
+    .map(|entry| entry.loader.version)
+    .collect())
+}
+
+/// Resolves the server launcher for a Fabric server: its Minecraft version, its pinned loader or
+/// the newest stable loader for that version, and the newest stable installer.
+pub async fn latest_fabric_server(
+    config: &FabricServer,
+    http_client: &Client,
+) -> crate::Result<FabricServerApp> {
+    let minecraft_version =
+        resolve_minecraft_version(&config.minecraft_version, http_client).await?;
+    let loader_version = match &config.loader_version {
+        Some(loader_version) => {
+            let loader_versions = fabric_loader_versions(&minecraft_version, http_client).await?;
+            if !loader_versions.contains(loader_version) {
+                return Err(Error::msg(format!(
+                    "Fabric loader {} is not available for Minecraft {}",
+                    loader_version, minecraft_version
+                )));
+            }
+            loader_version.clone()
+        }
+        None => latest_loader_version(&minecraft_version, http_client).await?,
+    };
+    let installer_version = latest_installer_version(http_client).await?;
+
+    Ok(FabricServerApp {
+        launcher_name: format!(
+            "fabric-server-mc.{}-loader.{}-launcher.{}.jar",
+            minecraft_version, loader_version, installer_version
+        ),
+        minecraft_version,
+        loader_version,
+        installer_version,
+    })
+}
+
+/// Downloads the server launcher. Fabric publishes no hash to check it against.
+pub async fn download_server_launcher(
+    server_app: &FabricServerApp,
+    launcher_file_path: &Path,
+    http_client: &Client,
+) -> crate::Result<()> {
+    download_file::<Sha256>(
+        &url::fabric_server_launcher_url(
+            &server_app.minecraft_version,
+            &server_app.loader_version,
+            &server_app.installer_version,
+        ),
+        launcher_file_path,
+        http_client,
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn resolve_minecraft_version(
+    minecraft_version: &str,
+    http_client: &Client,
+) -> crate::Result<String> {
+    if minecraft_version != LATEST_RELEASE && minecraft_version != LATEST_SNAPSHOT {
+        return Ok(minecraft_version.to_string());
+    }
+
+    // Game versions are listed newest first.
+    call_fabric_meta_api::<Vec<GameVersion>>(&url::fabric_game_versions_url(), http_client)
+        .await?
+        .into_iter()
+        .find(|game_version| game_version.stable || minecraft_version == LATEST_SNAPSHOT)
+        .map(|game_version| game_version.version)
+        .ok_or_else(|| Error::msg("Fabric supports no Minecraft versions"))
+}
+
+async fn latest_loader_version(
+    minecraft_version: &str,
+    http_client: &Client,
+) -> crate::Result<String> {
+    let loader_versions = call_fabric_meta_api::<Vec<LoaderVersionsEntry>>(
+        &url::fabric_loader_versions_url(minecraft_version),
+        http_client,
+    )
+    .await?;
+
+    // Loader versions are listed newest first; unstable ones are only used when no stable one is.
+    loader_versions
+        .iter()
+        .find(|entry| entry.loader.stable)
+        .or_else(|| loader_versions.first())
+        .map(|entry| entry.loader.version.clone())
+        .ok_or_else(|| {
+            Error::msg(format!(
+                "no Fabric loader is available for Minecraft {}",
+                minecraft_version
+            ))
+        })
+}
+
+async fn latest_installer_version(http_client: &Client) -> crate::Result<String> {
+    let installer_versions = call_fabric_meta_api::<Vec<InstallerVersion>>(
+        &url::fabric_installer_versions_url(),
+        http_client,
+    )
+    .await?;
+
+    installer_versions
+        .iter()
+        .find(|installer_version| installer_version.stable)
+        .or_else(|| installer_versions.first())
+        .map(|installer_version| installer_version.version.clone())
+        .ok_or_else(|| Error::msg("no Fabric installer is available"))
+}
+
+async fn call_fabric_meta_api<T: DeserializeOwned>(
+    url: &str,
+    http_client: &Client,
+) -> crate::Result<T> {
+    Ok(http_client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<T>()
+        .await?)
+}
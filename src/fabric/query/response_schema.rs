@@ -0,0 +1,29 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct GameVersion {
+    pub version: String,
+    pub stable: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct LoaderVersionsEntry {
+    pub loader: LoaderVersion,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct LoaderVersion {
+    pub version: String,
+    pub stable: bool,
+    pub build: i32,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct InstallerVersion {
+    pub version: String,
+    pub stable: bool,
+    pub url: String,
+}
@@ -0,0 +1,29 @@
+static FABRIC_META_BASE_URL: &str = "https://meta.fabricmc.net/v2";
+
+pub fn fabric_game_versions_url() -> String {
+    format!("{}/versions/game", FABRIC_META_BASE_URL)
+}
+
+pub fn fabric_installer_versions_url() -> String {
+    format!("{}/versions/installer", FABRIC_META_BASE_URL)
+}
+
+pub fn fabric_loader_versions_url(minecraft_version: &str) -> String {
+    format!(
+        "{}/versions/loader/{}",
+        FABRIC_META_BASE_URL, minecraft_version
+    )
+}
+
+pub fn fabric_server_launcher_url(
+    minecraft_version: &str,
+    loader_version: &str,
+    installer_version: &str,
+) -> String {
+    format!(
+        "{}/{}/{}/server/jar",
+        fabric_loader_versions_url(minecraft_version),
+        loader_version,
+        installer_version
+    )
+}
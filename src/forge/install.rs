@@ -0,0 +1,338 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::Error;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use reqwest::Client;
+use tokio::process::Command;
+
+use crate::config::constants::{INSTALLED_FILES_FILE_NAME_PREFIX, INSTALL_DIR_NAME_PREFIX};
+use crate::forge::{query, ForgeServerApp};
+use crate::server::updates::move_directory_contents;
+use crate::server::ServerApplication;
+
+#[cfg(unix)]
+static RUN_SCRIPT_NAME: &str = "run.sh";
+#[cfg(not(unix))]
+static RUN_SCRIPT_NAME: &str = "run.bat";
+static USER_JVM_ARGUMENTS_FILE_NAME: &str = "user_jvm_args.txt";
+
+/// Installs a server into `directory` by running its installer there, and records every file the
+/// installer created so that they can be removed again with [`remove_installed_files`].
+///
+/// The installer runs in an empty directory of its own, so that files an earlier install left
+/// behind are recorded too and survive when that install is removed. The JVM options the user
+/// edited in the server's working directory are kept, even when the server is installed elsewhere
+/// to be staged.
+pub async fn install_server(
+    server_app: &ForgeServerApp,
+    directory: &Path,
+    working_directory: &Path,
+    http_client: &Client,
+) -> crate::Result<()> {
+    let install_directory = directory.join(format!(
+        "{}{}",
+        INSTALL_DIR_NAME_PREFIX,
+        server_app.application_name()
+    ));
+    if install_directory.exists() {
+        fs::remove_dir_all(&install_directory)?;
+    }
+    fs::create_dir_all(&install_directory)?;
+
+    let installer_name = format!("{}-installer.jar", server_app.application_name());
+    query::download_installer(
+        server_app,
+        &install_directory.join(&installer_name),
+        http_client,
+    )
+    .await?;
+
+    println!(
+        "{} Running the {} installer...",
+        INFORMATION.glyph,
+        server_app.application_name()
+    );
+    let exit_status = Command::new("java")
+        .arg("-jar")
+        .arg(&installer_name)
+        .arg("--installServer")
+        .current_dir(&install_directory)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .await?;
+    if !exit_status.success() {
+        return Err(Error::msg(format!(
+            "installer exited with {}; its log is in {}",
+            exit_status,
+            install_directory.display()
+        )));
+    }
+    fs::remove_file(install_directory.join(&installer_name))?;
+
+    let installed_files = list_files(&install_directory, Path::new(""))?;
+    fs::write(
+        install_directory.join(installed_files_file_name(server_app)),
+        serde_json::to_string_pretty(&installed_files)?,
+    )?;
+    let kept_names = if working_directory
+        .join(USER_JVM_ARGUMENTS_FILE_NAME)
+        .exists()
+    {
+        vec![USER_JVM_ARGUMENTS_FILE_NAME]
+    } else {
+        vec![]
+    };
+    move_directory_contents(&install_directory, directory, &kept_names)?;
+    fs::remove_dir_all(&install_directory)?;
+
+    println!(
+        "{} Installed {} file(s) of {}",
+        CHECK_MARK.glyph,
+        installed_files.len(),
+        server_app.application_name()
+    );
+
+    Ok(())
+}
+
+/// Files the installer of a server created, relative to the directory it was installed in.
+pub fn installed_files(
+    server_app: &ForgeServerApp,
+    directory: &Path,
+) -> crate::Result<Vec<PathBuf>> {
+    let installed_files =
+        fs::read_to_string(directory.join(installed_files_file_name(server_app)))?;
+
+    Ok(serde_json::from_str(&installed_files)?)
+}
+
+/// Removes the files the installer of a server created, except those in `kept_files`, along with
+/// directories left empty.
+pub fn remove_installed_files(
+    server_app: &ForgeServerApp,
+    directory: &Path,
+    kept_files: &[PathBuf],
+) -> crate::Result<()> {
+    for installed_file in installed_files(server_app, directory)? {
+        if kept_files.contains(&installed_file) {
+            continue;
+        }
+
+        match fs::remove_file(directory.join(&installed_file)) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(Error::from(e)),
+        }
+
+        let mut parent = installed_file.parent();
+        while let Some(parent_directory) = parent.filter(|path| !path.as_os_str().is_empty()) {
+            // Fails, and so stops, at the first directory that is not empty.
+            if fs::remove_dir(directory.join(parent_directory)).is_err() {
+                break;
+            }
+            parent = parent_directory.parent();
+        }
+    }
+
+    fs::remove_file(directory.join(installed_files_file_name(server_app)))?;
+
+    Ok(())
+}
+
+/// Arguments that launch an installed server, split into the argument files that hold JVM options
+/// the user may edit and the rest. The configured JVM arguments go between the two, so that they
+/// apply on top of the user's options and before the main class.
+///
+/// Newer installers generate a run script that passes argument files to Java; older ones install a
+/// server jar instead.
+pub fn launch_arguments(
+    server_app: &ForgeServerApp,
+    directory: &Path,
+) -> crate::Result<(Vec<String>, Vec<String>)> {
+    let run_script = match fs::read_to_string(directory.join(RUN_SCRIPT_NAME)) {
+        Ok(run_script) => run_script,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Ok((
+                vec![],
+                vec![String::from("-jar"), server_jar(server_app, directory)?],
+            ))
+        }
+        Err(e) => return Err(Error::from(e)),
+    };
+
+    let (user_argument_files, launch_argument_files): (Vec<String>, Vec<String>) = run_script
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.to_lowercase().starts_with("rem "))
+        .flat_map(str::split_whitespace)
+        .map(|argument| argument.trim_matches('"'))
+        .filter(|argument| argument.starts_with('@'))
+        .map(|argument| argument.to_string())
+        .partition(|argument| argument.ends_with(USER_JVM_ARGUMENTS_FILE_NAME));
+
+    if launch_argument_files.is_empty() {
+        return Err(Error::msg(format!(
+            "{} passes no argument files to Java",
+            RUN_SCRIPT_NAME
+        )));
+    }
+
+    Ok((user_argument_files, launch_argument_files))
+}
+
+/// The jar an older installer created for the server, such as `forge-1.16.5-36.2.0.jar`.
+fn server_jar(server_app: &ForgeServerApp, directory: &Path) -> crate::Result<String> {
+    installed_files(server_app, directory)?
+        .into_iter()
+        .filter(|installed_file| installed_file.parent() == Some(Path::new("")))
+        .filter_map(|installed_file| installed_file.to_str().map(|name| name.to_string()))
+        .find(|name| {
+            name.starts_with(server_app.application_name())
+                && name.ends_with(".jar")
+                && !name.ends_with("-installer.jar")
+        })
+        .ok_or_else(|| {
+            Error::msg(format!(
+                "the installer of {} created neither {} nor a server jar",
+                server_app.application_name(),
+                RUN_SCRIPT_NAME
+            ))
+        })
+}
+
+fn installed_files_file_name(server_app: &ForgeServerApp) -> String {
+    format!(
+        "{}{}.json",
+        INSTALLED_FILES_FILE_NAME_PREFIX,
+        server_app.application_name()
+    )
+}
+
+fn list_files(root: &Path, relative_directory: &Path) -> crate::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for entry in fs::read_dir(root.join(relative_directory))? {
+        let entry = entry?;
+        let relative_path = relative_directory.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            files.extend(list_files(root, &relative_path)?);
+        } else {
+            files.push(relative_path);
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::{ForgeLoader, Installer};
+
+    fn server_app(name: &str) -> ForgeServerApp {
+        ForgeServerApp {
+            name: String::from(name),
+            loader: ForgeLoader::Forge,
+            minecraft_version: String::from("1.18.2"),
+            version: String::from("40.1.0"),
+            installer: Installer {
+                url: String::from(""),
+                sha1: vec![],
+            },
+        }
+    }
+
+    /// An empty directory for a test to install into, removed again when dropped.
+    struct TestDirectory(PathBuf);
+
+    impl TestDirectory {
+        fn new(test_name: &str) -> TestDirectory {
+            let directory = std::env::temp_dir().join(format!(
+                "stainless-forge-{}-{}",
+                test_name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&directory);
+            fs::create_dir_all(&directory).unwrap();
+            TestDirectory(directory)
+        }
+    }
+
+    impl Drop for TestDirectory {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn launches_newer_installs_with_the_argument_files_of_their_run_script() {
+        let directory = TestDirectory::new("run-script");
+        fs::write(
+            directory.0.join(RUN_SCRIPT_NAME),
+            "#!/usr/bin/env sh\n\
+             # Add custom JVM arguments to the user_jvm_args.txt\n\
+             java @user_jvm_args.txt @libraries/net/minecraftforge/forge/1.18.2-40.1.0/unix_args.txt \"$@\"\n",
+        )
+        .unwrap();
+
+        let (user_arguments, launch_arguments) =
+            launch_arguments(&server_app("forge-1.18.2-40.1.0"), &directory.0).unwrap();
+
+        assert_eq!(user_arguments, vec!["@user_jvm_args.txt"]);
+        assert_eq!(
+            launch_arguments,
+            vec!["@libraries/net/minecraftforge/forge/1.18.2-40.1.0/unix_args.txt"]
+        );
+    }
+
+    #[test]
+    fn keeps_the_user_arguments_file_ahead_of_the_launch_arguments() {
+        let directory = TestDirectory::new("user-arguments");
+        fs::write(
+            directory.0.join(RUN_SCRIPT_NAME),
+            "java \"@libraries/net/neoforged/neoforge/20.4.237/unix_args.txt\" \"@user_jvm_args.txt\"\n",
+        )
+        .unwrap();
+
+        let (user_arguments, launch_arguments) =
+            launch_arguments(&server_app("neoforge-20.4.237"), &directory.0).unwrap();
+
+        assert_eq!(user_arguments, vec!["@user_jvm_args.txt"]);
+        assert_eq!(
+            launch_arguments,
+            vec!["@libraries/net/neoforged/neoforge/20.4.237/unix_args.txt"]
+        );
+    }
+
+    #[test]
+    fn launches_older_installs_with_their_server_jar() {
+        let directory = TestDirectory::new("server-jar");
+        let server_app = server_app("forge-1.16.5-36.2.0");
+        fs::write(
+            directory.0.join(installed_files_file_name(&server_app)),
+            r#"["libraries/net/minecraftforge/forge/1.16.5-36.2.0/forge-1.16.5-36.2.0.jar",
+                "minecraft_server.1.16.5.jar", "forge-1.16.5-36.2.0.jar"]"#,
+        )
+        .unwrap();
+
+        let (user_arguments, launch_arguments) =
+            launch_arguments(&server_app, &directory.0).unwrap();
+
+        assert!(user_arguments.is_empty());
+        assert_eq!(launch_arguments, vec!["-jar", "forge-1.16.5-36.2.0.jar"]);
+    }
+
+    #[test]
+    fn rejects_run_scripts_without_argument_files() {
+        let directory = TestDirectory::new("no-argument-files");
+        fs::write(directory.0.join(RUN_SCRIPT_NAME), "java -jar server.jar\n").unwrap();
+
+        assert!(launch_arguments(&server_app("forge-1.18.2-40.1.0"), &directory.0).is_err());
+    }
+}
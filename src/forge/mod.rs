@@ -0,0 +1,216 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::process::ExitStatus;
+
+use async_trait::async_trait;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::server::process::{run_server_process, ProcessChannels};
+use crate::server::{Server, ServerApplication, ServerSettings};
+
+pub mod install;
+pub mod query;
+
+/// Distribution of Forge a server runs.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeLoader {
+    #[default]
+    Forge,
+    NeoForge,
+}
+
+impl Display for ForgeLoader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForgeLoader::Forge => write!(f, "Forge"),
+            ForgeLoader::NeoForge => write!(f, "NeoForge"),
+        }
+    }
+}
+
+/// A server running Forge or NeoForge, set up by their installer.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ForgeServer {
+    /// Distribution to install: `forge` or `neoforge`.
+    #[serde(default)]
+    pub loader: ForgeLoader,
+    /// Minecraft version to run, such as `1.18.2`.
+    pub minecraft_version: String,
+    /// Forge or NeoForge version to install, such as `40.1.0`. Defaults to the recommended Forge
+    /// version for the Minecraft version, or its newest NeoForge release, which the server is
+    /// updated to as they change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(flatten)]
+    pub settings: ServerSettings,
+}
+
+impl Server<ForgeServer, ForgeServerApp> for ForgeServer {
+    fn settings(&self) -> &ServerSettings {
+        &self.settings
+    }
+
+    fn default_version_check_client(&self) -> ForgeServerApp {
+        ForgeServerApp::default(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ForgeServerApp {
+    /// Name of the install, such as `forge-1.18.2-40.1.0`.
+    pub name: String,
+    pub loader: ForgeLoader,
+    pub minecraft_version: String,
+    pub version: String,
+    pub installer: Installer,
+}
+
+impl Display for ForgeServerApp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{Loader: {}, Minecraft: {}, Version: {}, Installer: {}}}",
+            self.loader, self.minecraft_version, self.version, self.installer
+        )
+    }
+}
+
+#[async_trait]
+impl ServerApplication<ForgeServer, ForgeServerApp> for ForgeServerApp {
+    fn application_name(&self) -> &str {
+        &self.name
+    }
+
+    fn build_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn matches_config(&self, config: &ForgeServer) -> bool {
+        config.loader == self.loader
+            && config.minecraft_version == self.minecraft_version
+            && config
+                .version
+                .iter()
+                .all(|version| *version == self.version)
+    }
+
+    async fn check_for_updated_server(
+        &self,
+        config: &ForgeServer,
+        http_client: &Client,
+    ) -> crate::Result<Option<ForgeServerApp>> {
+        let latest_client = query::latest_forge_server(config, http_client).await?;
+
+        // The recommended version may move back as well as forward, and the configuration may have
+        // changed, so any install other than the current one is an update.
+        if latest_client.name != self.name {
+            println!(
+                "{} Server {} is available",
+                CHECK_MARK.glyph, latest_client.name
+            );
+            Ok(Some(latest_client))
+        } else {
+            println!("{} No newer server is available!", CHECK_MARK.glyph);
+            Ok(None)
+        }
+    }
+
+    async fn download_server(
+        &self,
+        config: &ForgeServer,
+        directory: &Path,
+        http_client: &Client,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Downloading the {} installer...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        install::install_server(self, directory, config.working_directory(), http_client).await
+    }
+
+    fn delete_server(
+        &self,
+        config: &ForgeServer,
+        replacement: Option<&ForgeServerApp>,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Removing {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        // Both installs may share libraries and the run script, which must stay for the new one.
+        let kept_files = match replacement {
+            Some(replacement) => install::installed_files(replacement, config.working_directory())?,
+            None => vec![],
+        };
+
+        install::remove_installed_files(self, config.working_directory(), &kept_files)
+    }
+
+    async fn start_server(
+        &self,
+        server_config: &ForgeServer,
+        channels: ProcessChannels<'_>,
+    ) -> crate::Result<ExitStatus> {
+        println!(
+            "{} Starting {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        let (user_argument_files, launch_arguments) =
+            install::launch_arguments(self, server_config.working_directory())?;
+
+        let mut command = Command::new("java");
+        command
+            .args(user_argument_files)
+            .args(server_config.jvm_arguments())
+            .args(launch_arguments)
+            .arg("nogui")
+            .current_dir(server_config.working_directory());
+
+        run_server_process(self, server_config, command, channels).await
+    }
+}
+
+impl ForgeServerApp {
+    pub fn default(config: &ForgeServer) -> ForgeServerApp {
+        ForgeServerApp {
+            name: String::from(""),
+            loader: config.loader,
+            minecraft_version: config.minecraft_version.clone(),
+            version: String::from(""),
+            installer: Installer {
+                url: String::from(""),
+                sha1: vec![],
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Installer {
+    pub url: String,
+    pub sha1: Vec<u8>,
+}
+
+impl Display for Installer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{URL: {}, SHA1: {}}}",
+            self.url,
+            hex::encode(&self.sha1)
+        )
+    }
+}
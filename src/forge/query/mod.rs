@@ -0,0 +1,180 @@
+use std::path::Path;
+
+use anyhow::Error;
+use reqwest::Client;
+use sha1::Sha1;
+
+use crate::forge::query::response_schema::PromotionsResponse;
+use crate::forge::{ForgeLoader, ForgeServer, ForgeServerApp, Installer};
+use crate::server::download::download_verified_file;
+
+mod response_schema;
+mod url;
+
+/// Versions of the loader that are available for a Minecraft version, oldest first.
+pub async fn forge_versions(
+    loader: ForgeLoader,
+    minecraft_version: &str,
+    http_client: &Client,
+) -> crate::Result<Vec<String>> {
+    let versions_url = match loader {
+        ForgeLoader::Forge => url::forge_versions_url(),
+        ForgeLoader::NeoForge => url::neoforge_versions_url(),
+    };
+    let maven_metadata = call_text_api(&versions_url, http_client).await?;
+
+    Ok(match loader {
+        ForgeLoader::Forge => {
+            let version_prefix = format!("{}-", minecraft_version);
+            maven_metadata_versions(&maven_metadata)
+                .filter_map(|version| version.strip_prefix(&version_prefix))
+                .map(|version| version.to_string())
+                .collect()
+        }
+        ForgeLoader::NeoForge => {
+            let version_prefix = neoforge_version_prefix(minecraft_version);
+            maven_metadata_versions(&maven_metadata)
+                .filter(|version| version.starts_with(&version_prefix))
+                .map(|version| version.to_string())
+                .collect()
+        }
+    })
+}
+
+/// Resolves the installer of a Forge server: its pinned version, or the recommended Forge version
+/// or newest NeoForge version for its Minecraft version.
+pub async fn latest_forge_server(
+    config: &ForgeServer,
+    http_client: &Client,
+) -> crate::Result<ForgeServerApp> {
+    let version = match &config.version {
+        Some(version) => {
+            let versions =
+                forge_versions(config.loader, &config.minecraft_version, http_client).await?;
+            if !versions.contains(version) {
+                return Err(Error::msg(format!(
+                    "{} {} is not available for Minecraft {}",
+                    config.loader, version, config.minecraft_version
+                )));
+            }
+            version.clone()
+        }
+        None => match config.loader {
+            ForgeLoader::Forge => {
+                promoted_forge_version(&config.minecraft_version, http_client).await?
+            }
+            ForgeLoader::NeoForge => {
+                newest_neoforge_version(&config.minecraft_version, http_client).await?
+            }
+        },
+    };
+
+    let installer_url = match config.loader {
+        ForgeLoader::Forge => url::forge_installer_url(&config.minecraft_version, &version),
+        ForgeLoader::NeoForge => url::neoforge_installer_url(&version),
+    };
+    let sha1 = call_text_api(&url::sha1_url(&installer_url), http_client).await?;
+    let sha1 = sha1.split_whitespace().next().unwrap_or_default();
+
+    Ok(ForgeServerApp {
+        name: match config.loader {
+            ForgeLoader::Forge => format!("forge-{}-{}", config.minecraft_version, version),
+            ForgeLoader::NeoForge => format!("neoforge-{}", version),
+        },
+        loader: config.loader,
+        minecraft_version: config.minecraft_version.clone(),
+        version,
+        installer: Installer {
+            url: installer_url,
+            sha1: hex::decode(sha1)?,
+        },
+    })
+}
+
+pub async fn download_installer(
+    server_app: &ForgeServerApp,
+    installer_file_path: &Path,
+    http_client: &Client,
+) -> crate::Result<()> {
+    download_verified_file::<Sha1>(
+        &server_app.installer.url,
+        installer_file_path,
+        &server_app.installer.sha1,
+        http_client,
+    )
+    .await
+}
+
+async fn promoted_forge_version(
+    minecraft_version: &str,
+    http_client: &Client,
+) -> crate::Result<String> {
+    let promotions = http_client
+        .get(url::forge_promotions_url())
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<PromotionsResponse>()
+        .await?;
+
+    match promotions.promoted_version(minecraft_version) {
+        Some(version) => Ok(version.clone()),
+        None => Err(Error::msg(format!(
+            "Forge has no recommended or latest version for Minecraft {}",
+            minecraft_version
+        ))),
+    }
+}
+
+async fn newest_neoforge_version(
+    minecraft_version: &str,
+    http_client: &Client,
+) -> crate::Result<String> {
+    let versions = forge_versions(ForgeLoader::NeoForge, minecraft_version, http_client).await?;
+
+    // Versions are listed oldest first, and betas only count while there is no release yet.
+    versions
+        .iter()
+        .rev()
+        .find(|version| !version.ends_with("-beta"))
+        .or_else(|| versions.last())
+        .cloned()
+        .ok_or_else(|| {
+            Error::msg(format!(
+                "NeoForge has no version for Minecraft {}",
+                minecraft_version
+            ))
+        })
+}
+
+/// NeoForge numbers its versions after the Minecraft version without its leading `1.`, so
+/// Minecraft 1.20.4 has NeoForge versions `20.4.*` and Minecraft 1.21 has `21.0.*`.
+fn neoforge_version_prefix(minecraft_version: &str) -> String {
+    let version = minecraft_version
+        .strip_prefix("1.")
+        .unwrap_or(minecraft_version);
+
+    if version.contains('.') {
+        format!("{}.", version)
+    } else {
+        format!("{}.0.", version)
+    }
+}
+
+fn maven_metadata_versions(maven_metadata: &str) -> impl Iterator<Item = &str> {
+    maven_metadata
+        .split("<version>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</version>"))
+        .map(|(version, _)| version.trim())
+}
+
+async fn call_text_api(url: &str, http_client: &Client) -> crate::Result<String> {
+    Ok(http_client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct PromotionsResponse {
+    pub homepage: String,
+    /// Forge version of each promotion, keyed by `<minecraft version>-recommended` or
+    /// `<minecraft version>-latest`.
+    pub promos: HashMap<String, String>,
+}
+
+impl PromotionsResponse {
+    /// The recommended Forge version for a Minecraft version, or its latest when none is
+    /// recommended yet.
+    pub fn promoted_version(&self, minecraft_version: &str) -> Option<&String> {
+        self.promos
+            .get(&format!("{}-recommended", minecraft_version))
+            .or_else(|| self.promos.get(&format!("{}-latest", minecraft_version)))
+    }
+}
@@ -0,0 +1,38 @@
+static FORGE_PROMOTIONS_URL: &str =
+    "https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json";
+static FORGE_MAVEN_URL: &str = "https://maven.minecraftforge.net/net/minecraftforge/forge";
+static NEOFORGE_MAVEN_URL: &str = "https://maven.neoforged.net/releases/net/neoforged/neoforge";
+
+pub fn forge_promotions_url() -> String {
+    FORGE_PROMOTIONS_URL.to_string()
+}
+
+pub fn forge_versions_url() -> String {
+    format!("{}/maven-metadata.xml", FORGE_MAVEN_URL)
+}
+
+pub fn neoforge_versions_url() -> String {
+    format!("{}/maven-metadata.xml", NEOFORGE_MAVEN_URL)
+}
+
+/// Forge versions are published under their Minecraft version, such as `1.18.2-40.1.0`.
+pub fn forge_installer_url(minecraft_version: &str, forge_version: &str) -> String {
+    let maven_version = format!("{}-{}", minecraft_version, forge_version);
+
+    format!(
+        "{}/{}/forge-{}-installer.jar",
+        FORGE_MAVEN_URL, maven_version, maven_version
+    )
+}
+
+pub fn neoforge_installer_url(neoforge_version: &str) -> String {
+    format!(
+        "{}/{}/neoforge-{}-installer.jar",
+        NEOFORGE_MAVEN_URL, neoforge_version, neoforge_version
+    )
+}
+
+/// Maven publishes the SHA-1 of every file next to it.
+pub fn sha1_url(file_url: &str) -> String {
+    format!("{}.sha1", file_url)
+}
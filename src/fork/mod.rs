@@ -0,0 +1,207 @@
+use std::fmt::{Display, Formatter};
+use std::fs::remove_file;
+use std::path::Path;
+use std::process::ExitStatus;
+
+use async_trait::async_trait;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::fork::purpur::PurpurDistributor;
+use crate::server::distributor::{download_build, BuildDistributor, Checksum, DistributedBuild};
+use crate::server::process::{run_server_process, ProcessChannels};
+use crate::server::{Server, ServerApplication, ServerSettings};
+
+pub mod purpur;
+
+static PURPUR_DISTRIBUTOR: PurpurDistributor = PurpurDistributor;
+
+/// Fork of Paper whose builds a server runs.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForkProject {
+    Purpur,
+}
+
+impl ForkProject {
+    /// Distributor that publishes the fork's builds.
+    pub fn distributor(&self) -> &'static dyn BuildDistributor {
+        match self {
+            ForkProject::Purpur => &PURPUR_DISTRIBUTOR,
+        }
+    }
+}
+
+impl Display for ForkProject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.distributor().name())
+    }
+}
+
+/// A server running a fork of Paper, such as Purpur, downloaded from its own API.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ForkServer {
+    /// Fork to run, such as `purpur`.
+    pub project: ForkProject,
+    /// Minecraft version of the fork, such as `1.18.2`.
+    pub version: String,
+    #[serde(flatten)]
+    pub settings: ServerSettings,
+}
+
+impl Server<ForkServer, ForkServerApp> for ForkServer {
+    fn settings(&self) -> &ServerSettings {
+        &self.settings
+    }
+
+    fn default_version_check_client(&self) -> ForkServerApp {
+        ForkServerApp::default(self)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ForkServerApp {
+    pub project: ForkProject,
+    pub version: String,
+    pub build: DistributedBuild,
+}
+
+impl Display for ForkServerApp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{Project: {}, Version: {}, Build: {}}}",
+            self.project, self.version, self.build
+        )
+    }
+}
+
+#[async_trait]
+impl ServerApplication<ForkServer, ForkServerApp> for ForkServerApp {
+    fn application_name(&self) -> &str {
+        &self.build.jar_name
+    }
+
+    fn build_name(&self) -> String {
+        format!(
+            "{}-{}-{}",
+            self.project.distributor().name().to_lowercase(),
+            self.version,
+            self.build.number
+        )
+    }
+
+    fn matches_config(&self, config: &ForkServer) -> bool {
+        config.project == self.project && config.version == self.version
+    }
+
+    async fn check_for_updated_server(
+        &self,
+        config: &ForkServer,
+        http_client: &Client,
+    ) -> crate::Result<Option<ForkServerApp>> {
+        let latest_build = config
+            .project
+            .distributor()
+            .latest_build(&config.version, http_client)
+            .await?;
+
+        // A changed version restarts build numbers, so any build of it is an update.
+        if config.project != self.project
+            || config.version != self.version
+            || latest_build.number > self.build.number
+        {
+            println!(
+                "{} Newer server build is available: {}",
+                CHECK_MARK.glyph, latest_build.number
+            );
+            Ok(Some(ForkServerApp {
+                project: config.project,
+                version: config.version.clone(),
+                build: latest_build,
+            }))
+        } else {
+            println!("{} No newer server is available!", CHECK_MARK.glyph);
+            Ok(None)
+        }
+    }
+
+    async fn download_server(
+        &self,
+        _config: &ForkServer,
+        directory: &Path,
+        http_client: &Client,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Downloading {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        download_build(
+            self.project.distributor(),
+            &self.version,
+            &self.build,
+            &directory.join(self.application_name()),
+            http_client,
+        )
+        .await
+    }
+
+    fn delete_server(
+        &self,
+        config: &ForkServer,
+        _replacement: Option<&ForkServerApp>,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Removing {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        remove_file(config.working_directory().join(self.application_name()))?;
+
+        Ok(())
+    }
+
+    async fn start_server(
+        &self,
+        server_config: &ForkServer,
+        channels: ProcessChannels<'_>,
+    ) -> crate::Result<ExitStatus> {
+        println!(
+            "{} Starting {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        let mut command = Command::new("java");
+        command
+            .args(server_config.jvm_arguments())
+            .arg("-jar")
+            .arg(self.application_name())
+            .arg("nogui")
+            .current_dir(server_config.working_directory());
+
+        run_server_process(self, server_config, command, channels).await
+    }
+}
+
+impl ForkServerApp {
+    pub fn default(config: &ForkServer) -> ForkServerApp {
+        ForkServerApp {
+            project: config.project,
+            version: config.version.clone(),
+            build: DistributedBuild {
+                number: -1,
+                jar_name: String::from(""),
+                checksum: Checksum::Sha256(vec![]),
+            },
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+
+use crate::fork::purpur::response_schema::{BuildResponse, ProjectResponse, VersionResponse};
+use crate::server::distributor::{BuildDistributor, Checksum, DistributedBuild};
+
+mod response_schema;
+mod url;
+
+static PURPUR_SUCCESSFUL_BUILD_RESULT: &str = "SUCCESS";
+
+/// Purpur's builds, published through api.purpurmc.org with an MD5 checksum for each jar.
+pub struct PurpurDistributor;
+
+#[async_trait]
+impl BuildDistributor for PurpurDistributor {
+    fn name(&self) -> &str {
+        "Purpur"
+    }
+
+    async fn versions(&self, http_client: &Client) -> crate::Result<Vec<String>> {
+        Ok(
+            call_purpur_api::<ProjectResponse>(&url::purpur_project_url(), http_client)
+                .await?
+                .versions,
+        )
+    }
+
+    async fn latest_build(
+        &self,
+        version: &str,
+        http_client: &Client,
+    ) -> crate::Result<DistributedBuild> {
+        let version_response =
+            call_purpur_api::<VersionResponse>(&url::purpur_version_url(version), http_client)
+                .await?;
+
+        // Failed builds are listed too, and are skipped until one that succeeded is found.
+        for build in builds_newest_first(&version_response.builds.all) {
+            let build_response = call_purpur_api::<BuildResponse>(
+                &url::purpur_build_url(version, build),
+                http_client,
+            )
+            .await?;
+
+            if let Some(distributed_build) = successful_build(version, build, build_response)? {
+                return Ok(distributed_build);
+            }
+        }
+
+        Err(Error::msg(format!(
+            "no successful Purpur builds found for {}",
+            version
+        )))
+    }
+
+    fn download_url(&self, version: &str, build: &DistributedBuild) -> String {
+        url::purpur_download_url(version, build.number)
+    }
+}
+
+/// Build numbers of a version from newest to oldest, leaving out any that are not numbers.
+fn builds_newest_first(builds: &[String]) -> Vec<i32> {
+    let mut builds: Vec<i32> = builds
+        .iter()
+        .filter_map(|build| build.parse().ok())
+        .collect();
+    builds.sort_unstable_by(|a, b| b.cmp(a));
+    builds
+}
+
+/// The jar of a build, or `None` when the build failed and so has none to download.
+fn successful_build(
+    version: &str,
+    build: i32,
+    build_response: BuildResponse,
+) -> crate::Result<Option<DistributedBuild>> {
+    if build_response.result != PURPUR_SUCCESSFUL_BUILD_RESULT {
+        return Ok(None);
+    }
+
+    Ok(match build_response.md5 {
+        Some(md5) => Some(DistributedBuild {
+            number: build,
+            jar_name: format!("purpur-{}-{}.jar", version, build),
+            checksum: Checksum::Md5(hex::decode(md5)?),
+        }),
+        None => None,
+    })
+}
+
+async fn call_purpur_api<T: DeserializeOwned>(url: &str, http_client: &Client) -> crate::Result<T> {
+    Ok(http_client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<T>()
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_response(build: i32, result: &str, md5: Option<&str>) -> BuildResponse {
+        BuildResponse {
+            project: String::from("purpur"),
+            version: String::from("1.18.2"),
+            build: build.to_string(),
+            result: String::from(result),
+            md5: md5.map(String::from),
+        }
+    }
+
+    #[test]
+    fn picks_the_newest_successful_build_of_a_mixed_list() {
+        let builds: Vec<String> = ["1630", "1633", "1631", "1632"]
+            .iter()
+            .map(|build| build.to_string())
+            .collect();
+        let responses = |build: i32| match build {
+            1633 => build_response(build, "FAILURE", None),
+            // Succeeded, but without a checksum to verify a download with.
+            1632 => build_response(build, "SUCCESS", None),
+            _ => build_response(build, "SUCCESS", Some("0123456789abcdef0123456789abcdef")),
+        };
+
+        let newest_first = builds_newest_first(&builds);
+        let distributed_build = newest_first
+            .iter()
+            .find_map(|build| successful_build("1.18.2", *build, responses(*build)).unwrap())
+            .unwrap();
+
+        assert_eq!(newest_first, vec![1633, 1632, 1631, 1630]);
+        assert_eq!(distributed_build.number, 1631);
+        assert_eq!(distributed_build.jar_name, "purpur-1.18.2-1631.jar");
+    }
+
+    #[test]
+    fn leaves_out_builds_that_are_not_numbers() {
+        let builds = vec![String::from("1632"), String::from("latest")];
+
+        assert_eq!(builds_newest_first(&builds), vec![1632]);
+    }
+}
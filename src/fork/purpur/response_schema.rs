@@ -0,0 +1,36 @@
+use serde::Deserialize;
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct ProjectResponse {
+    pub project: String,
+    pub versions: Vec<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct VersionResponse {
+    pub project: String,
+    pub version: String,
+    pub builds: Builds,
+}
+
+/// Builds of a version. Purpur numbers builds with strings.
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct Builds {
+    pub latest: String,
+    pub all: Vec<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct BuildResponse {
+    pub project: String,
+    pub version: String,
+    pub build: String,
+    pub result: String,
+    /// Missing for builds that failed.
+    #[serde(default)]
+    pub md5: Option<String>,
+}
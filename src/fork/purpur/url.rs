@@ -0,0 +1,17 @@
+static PURPUR_API_BASE_URL: &str = "https://api.purpurmc.org/v2/purpur";
+
+pub fn purpur_project_url() -> String {
+    PURPUR_API_BASE_URL.to_string()
+}
+
+pub fn purpur_version_url(version: &str) -> String {
+    format!("{}/{}", PURPUR_API_BASE_URL, version)
+}
+
+pub fn purpur_build_url(version: &str, build: i32) -> String {
+    format!("{}/{}", purpur_version_url(version), build)
+}
+
+pub fn purpur_download_url(version: &str, build: i32) -> String {
+    format!("{}/download", purpur_build_url(version, build))
+}
@@ -1,29 +1,110 @@
 use anyhow::Error;
-use emoji::symbols::other_symbol::CROSS_MARK;
+use clap::Parser;
+use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
 use reqwest::Client;
 
-use crate::papermc::{PaperMCServer, PaperMCServerApp};
+use crate::cli::{Cli, Command, ConfigCommand, RunArgs, ServerSelection};
+use crate::config::StainlessPaths;
+use crate::papermc::PaperMCServer;
 
+mod cli;
 mod config;
+mod custom;
+mod fabric;
+mod forge;
+mod fork;
 mod papermc;
 mod server;
+mod vanilla;
 
 type Result<T> = std::result::Result<T, Error>;
 
 #[tokio::main]
 async fn main() {
+    let cli = Cli::parse();
     let http_client = Client::new();
 
-    let stainless_config = match config::load_stainless_config(&http_client).await {
-        Ok(config) => config,
-        Err(e) => {
-            println!(
-                "{} Error occurred while loading Stainless configuration: {}",
-                CROSS_MARK.glyph, e
+    if let Err(e) = run_command(cli, &http_client).await {
+        println!("{} {}", CROSS_MARK.glyph, e);
+        std::process::exit(1);
+    }
+
+    // Exit explicitly: the console reader may still be blocked reading stdin, and the runtime
+    // would otherwise wait for it before shutting down.
+    std::process::exit(0);
+}
+
+async fn run_command(cli: Cli, http_client: &Client) -> Result<()> {
+    let paths = StainlessPaths::new(cli.home, cli.config, cli.profile);
+    let command = cli
+        .command
+        .unwrap_or_else(|| Command::Run(RunArgs::default()));
+
+    match command {
+        Command::Init(args) => {
+            config::init_stainless_config(http_client, &paths, args.force, args.defaults).await
+        }
+        Command::Schema(args) => config::write_stainless_config_schema(args.output.as_deref()),
+        Command::Config(ConfigCommand::Migrate(args)) => {
+            config::migrate_stainless_config(&paths, args.write)
+        }
+        Command::Config(ConfigCommand::Show(args)) => {
+            config::show_stainless_config(&paths, args.resolved)
+        }
+        Command::Validate(args) => {
+            let stainless_config =
+                load_selected_servers(&paths, &ServerSelection::default(), false)?;
+
+            if !args.offline {
+                config::validate_stainless_config_online(&paths, &stainless_config, http_client)
+                    .await?;
+            }
+
+            println!("{} Configuration is valid!", CHECK_MARK.glyph);
+            Ok(())
+        }
+        Command::Run(args) => {
+            let stainless_config = load_selected_servers(&paths, &args.selection, true)?;
+            config::validate_stainless_config_online(&paths, &stainless_config, http_client)
+                .await?;
+
+            let config_updates = config::reload::watch_stainless_config(
+                paths,
+                args.selection.servers,
+                stainless_config,
             );
-            return;
+            server::begin_server_task(config_updates, http_client, args.events_file).await
+        }
+        Command::Check(selection) => {
+            let stainless_config = load_selected_servers(&paths, &selection, false)?;
+
+            server::commands::check_servers(&stainless_config, http_client).await
+        }
+        Command::Update(selection) => {
+            let stainless_config = load_selected_servers(&paths, &selection, false)?;
+
+            server::commands::update_servers(&stainless_config, http_client).await
         }
-    };
+        Command::Status(args) => {
+            let stainless_config = load_selected_servers(&paths, &args.selection, false)?;
+
+            server::commands::show_server_statuses(&stainless_config, http_client, args.offline)
+                .await
+        }
+    }
+}
+
+fn load_selected_servers(
+    paths: &StainlessPaths,
+    selection: &ServerSelection,
+    generate_missing: bool,
+) -> Result<config::StainlessConfig> {
+    let stainless_config = config::load_stainless_config(paths, generate_missing).map_err(|e| {
+        Error::msg(format!(
+            "Error occurred while loading Stainless configuration: {}",
+            e
+        ))
+    })?;
 
-    server::begin_server_task(&stainless_config.server, &http_client).await
+    stainless_config.select_servers(&selection.servers)
 }
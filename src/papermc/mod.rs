@@ -1,59 +1,57 @@
 use std::fmt::{Display, Formatter};
-use std::fs::{File, remove_file};
+use std::fs::remove_file;
 use std::path::Path;
-use std::process::{ExitStatus, Stdio};
+use std::process::ExitStatus;
 
-use anyhow::Error;
 use async_trait::async_trait;
 use emoji::symbols::alphanum::INFORMATION;
-use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
+use emoji::symbols::other_symbol::CHECK_MARK;
 use reqwest::Client;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tokio::{pin, select};
-use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
-use tokio::sync::mpsc::Receiver;
 
-use crate::config::constants::SERVER_INFO_DIR_PATH;
-use crate::server::{Server, ServerApplication};
+use crate::server::process::{run_server_process, ProcessChannels};
+use crate::server::{Server, ServerApplication, ServerSettings};
 
 pub mod query;
 
-#[derive(Serialize, Deserialize)]
+/// A server running a project published by PaperMC, such as Paper, Waterfall or Velocity.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct PaperMCServer {
-    pub server_name: String,
+    /// PaperMC project and version to run.
     pub project: PaperMCProject,
-    pub jvm_arguments: Vec<String>,
+    #[serde(flatten)]
+    pub settings: ServerSettings,
 }
 
 impl Server<PaperMCServer, PaperMCServerApp> for PaperMCServer {
-    fn server_name(&self) -> &str {
-        self.server_name.as_str()
+    fn settings(&self) -> &ServerSettings {
+        &self.settings
     }
 
-    fn jvm_arguments(&self) -> &Vec<String> {
-        self.jvm_arguments.as_ref()
+    fn stop_command(&self) -> &str {
+        match self.project.name.as_str() {
+            "waterfall" | "travertine" => "end",
+            "velocity" => "shutdown",
+            _ => "stop",
+        }
     }
 
-    fn load_saved_server_app(&self) -> crate::Result<PaperMCServerApp> {
-        let client_info_path = self.client_info_file_path();
-        let saved_client_path = Path::new(&client_info_path);
-        let mut saved_client_file = File::open(saved_client_path)?;
-        let save_config = bincode::config::standard().write_fixed_array_length();
-        let saved_client: PaperMCServerApp =
-            bincode::serde::decode_from_std_read(&mut saved_client_file, save_config)?;
-
-        println!(
-            "{} Found existing server: {}",
-            CHECK_MARK.glyph,
-            saved_client.application_name()
-        );
-
-        Ok(saved_client)
+    fn save_command(&self) -> Option<&str> {
+        match self.project.name.as_str() {
+            "waterfall" | "travertine" | "velocity" => None,
+            _ => Some("save-all"),
+        }
     }
 
-    fn client_info_file_path(&self) -> String {
-        format!("{}/{}", SERVER_INFO_DIR_PATH, self.server_name)
+    fn broadcast_command(&self) -> Option<&str> {
+        match self.project.name.as_str() {
+            "waterfall" | "travertine" => Some("alert"),
+            "velocity" => None,
+            _ => Some("say"),
+        }
     }
 
     fn default_version_check_client(&self) -> PaperMCServerApp {
@@ -61,9 +59,12 @@ impl Server<PaperMCServer, PaperMCServerApp> for PaperMCServer {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct PaperMCProject {
+    /// PaperMC project to run, such as `paper`, `waterfall` or `velocity`.
     pub name: String,
+    /// Minecraft version of the project, such as `1.18.1`.
     pub version: String,
 }
 
@@ -96,15 +97,28 @@ impl ServerApplication<PaperMCServer, PaperMCServerApp> for PaperMCServerApp {
         &self.application_download.name
     }
 
+    fn build_name(&self) -> String {
+        format!(
+            "{}-{}-{}",
+            self.project.name, self.project.version, self.build
+        )
+    }
+
+    fn matches_config(&self, config: &PaperMCServer) -> bool {
+        self.project == config.project
+    }
+
     async fn check_for_updated_server(
         &self,
-        _config: &PaperMCServer,
+        config: &PaperMCServer,
         http_client: &Client,
     ) -> crate::Result<Option<PaperMCServerApp>> {
         let latest_client =
-            query::latest_papermc_server_for_project(&self.project, http_client).await?;
+            query::latest_papermc_server_for_project(&config.project, http_client).await?;
 
-        if latest_client.build > self.build {
+        // Builds are numbered per project version, so any build of a newly configured project or
+        // version is an update.
+        if latest_client.project != self.project || latest_client.build > self.build {
             println!(
                 "{} Newer server build is available: {}",
                 CHECK_MARK.glyph, latest_client.build
@@ -116,39 +130,38 @@ impl ServerApplication<PaperMCServer, PaperMCServerApp> for PaperMCServerApp {
         }
     }
 
-    async fn download_server(&self, http_client: &Client) -> crate::Result<()> {
+    async fn download_server(
+        &self,
+        _config: &PaperMCServer,
+        directory: &Path,
+        http_client: &Client,
+    ) -> crate::Result<()> {
         println!(
             "{} Downloading {}...",
             INFORMATION.glyph,
             self.application_name()
         );
 
-        query::download_server_application(self, Path::new(&self.application_name()), http_client)
-            .await
+        query::download_server_application(
+            self,
+            &directory.join(self.application_name()),
+            http_client,
+        )
+        .await
     }
 
-    fn delete_server(&self) -> crate::Result<()> {
+    fn delete_server(
+        &self,
+        config: &PaperMCServer,
+        _replacement: Option<&PaperMCServerApp>,
+    ) -> crate::Result<()> {
         println!(
             "{} Removing {}...",
             INFORMATION.glyph,
             self.application_name()
         );
 
-        remove_file(Path::new(&self.application_name()))?;
-
-        Ok(())
-    }
-
-    fn save_server_info(&self, client_config: &PaperMCServer) -> crate::Result<()> {
-        let mut client_info_file = File::create(Path::new(&client_config.client_info_file_path()))?;
-        let save_config = bincode::config::standard().write_fixed_array_length();
-        bincode::serde::encode_into_std_write(self, &mut client_info_file, save_config)?;
-
-        Ok(())
-    }
-
-    fn delete_server_info(&self, client_config: &PaperMCServer) -> crate::Result<()> {
-        std::fs::remove_file(Path::new(&client_config.client_info_file_path()))?;
+        remove_file(config.working_directory().join(self.application_name()))?;
 
         Ok(())
     }
@@ -156,7 +169,7 @@ impl ServerApplication<PaperMCServer, PaperMCServerApp> for PaperMCServerApp {
     async fn start_server(
         &self,
         server_config: &PaperMCServer,
-        input_receiver: &mut Receiver<u8>,
+        channels: ProcessChannels<'_>,
     ) -> crate::Result<ExitStatus> {
         println!(
             "{} Starting {}...",
@@ -164,40 +177,15 @@ impl ServerApplication<PaperMCServer, PaperMCServerApp> for PaperMCServerApp {
             self.application_name()
         );
 
-        let mut server_process = Command::new("java")
+        let mut command = Command::new("java");
+        command
+            .args(server_config.jvm_arguments())
             .arg("-jar")
-            .arg(&self.application_name())
+            .arg(self.application_name())
             .arg("nogui")
-            .args(server_config.jvm_arguments())
-            .stdin(Stdio::piped())
-            .spawn()?;
-
-        let mut child_in = server_process.stdin.take().unwrap();
-
-        let server_task = server_process.wait();
-        pin!(server_task);
-
-        loop {
-            select! {
-                receive_result = input_receiver.recv() => {
-                    match receive_result {
-                        Some(byte) => child_in.write_u8(byte).await?,
-                        None => return Err(Error::msg("Input channel broke."))
-                    }
-                }
-                server_result = &mut server_task => {
-                    match server_result {
-                        Ok(output) => {
-                            return Ok(output)
-                        },
-                        Err(e) => {
-                            println!("{} Error occurred running server: {}", CROSS_MARK.glyph, e);
-                            return Err(Error::from(e))
-                        }
-                    }
-                }
-            }
-        }
+            .current_dir(server_config.working_directory());
+
+        run_server_process(self, server_config, command, channels).await
     }
 }
 
@@ -1,38 +1,104 @@
-use std::fs::File;
-use std::io::Write;
 use std::path::Path;
 
 use anyhow::Error;
-use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
-use indicatif::{ProgressBar, ProgressStyle};
+use async_trait::async_trait;
 use reqwest::Client;
-use sha2::{Digest, Sha256};
 
-use crate::config::constants::DOWNLOAD_PROGRESS_BAR_TEMPLATE;
-use crate::papermc::{Download, PaperMCProject, PaperMCServerApp};
 use crate::papermc::query::response_schema::{
-    BuildResponse, Download as SchemaDownload, VersionResponse,
+    BuildResponse, Download as SchemaDownload, ProjectResponse, ProjectsResponse, VersionResponse,
 };
+use crate::papermc::{Download, PaperMCProject, PaperMCServerApp};
+use crate::server::distributor::{download_build, BuildDistributor, Checksum, DistributedBuild};
 
 mod response_schema;
 mod url;
 
+pub async fn papermc_projects(http_client: &Client) -> crate::Result<Vec<String>> {
+    Ok(http_client
+        .get(url::papermc_projects_url())
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<ProjectsResponse>()
+        .await?
+        .projects)
+}
+
+pub async fn papermc_project_versions(
+    project_name: &str,
+    http_client: &Client,
+) -> crate::Result<Vec<String>> {
+    Ok(http_client
+        .get(url::papermc_project_url(project_name))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<ProjectResponse>()
+        .await?
+        .versions)
+}
+
+/// Builds of a PaperMC project, published through papermc.io with a SHA-256 checksum for each jar.
+pub struct PaperMCDistributor<'a> {
+    pub project_name: &'a str,
+}
+
+#[async_trait]
+impl BuildDistributor for PaperMCDistributor<'_> {
+    fn name(&self) -> &str {
+        "PaperMC"
+    }
+
+    async fn versions(&self, http_client: &Client) -> crate::Result<Vec<String>> {
+        papermc_project_versions(self.project_name, http_client).await
+    }
+
+    async fn latest_build(
+        &self,
+        version: &str,
+        http_client: &Client,
+    ) -> crate::Result<DistributedBuild> {
+        let project = PaperMCProject {
+            name: self.project_name.to_string(),
+            version: version.to_string(),
+        };
+        let latest_build = latest_project_build(&project, http_client).await?;
+        let application_download =
+            application_build_download(&project, latest_build, http_client).await?;
+
+        Ok(DistributedBuild {
+            number: latest_build,
+            jar_name: application_download.name,
+            checksum: Checksum::Sha256(hex::decode(application_download.sha256)?),
+        })
+    }
+
+    fn download_url(&self, version: &str, build: &DistributedBuild) -> String {
+        url::papermc_project_download_url(self.project_name, version, build.number, &build.jar_name)
+    }
+}
+
 pub async fn latest_papermc_server_for_project(
     project: &PaperMCProject,
     http_client: &Client,
 ) -> crate::Result<PaperMCServerApp> {
-    let latest_build = latest_project_build(project, http_client).await?;
-    let application_download =
-        application_build_download(project, latest_build, http_client).await?;
-
-    Ok(PaperMCServerApp {
-        project: project.clone(),
-        build: latest_build,
-        application_download: Download {
-            name: application_download.name,
-            sha256: hex::decode(application_download.sha256)?,
-        },
-    })
+    let latest_build = PaperMCDistributor {
+        project_name: &project.name,
+    }
+    .latest_build(&project.version, http_client)
+    .await?;
+
+    match latest_build.checksum {
+        Checksum::Sha256(sha256) => Ok(PaperMCServerApp {
+            project: project.clone(),
+            build: latest_build.number,
+            application_download: Download {
+                name: latest_build.jar_name,
+                sha256,
+            },
+        }),
+        Checksum::Md5(_) => Err(Error::msg("PaperMC published no SHA-256 for the build")),
+    }
 }
 
 async fn application_build_download(
@@ -90,39 +156,22 @@ async fn call_papermc_project_build_api(
 }
 
 pub async fn download_server_application(
-    project: &PaperMCServerApp,
+    server_app: &PaperMCServerApp,
     client_file_path: &Path,
     http_client: &Client,
 ) -> crate::Result<()> {
-    let mut response = http_client
-        .get(url::papermc_project_download_url(project))
-        .send()
-        .await?
-        .error_for_status()?;
-
-    let content_length = match response.content_length() {
-        Some(len) => len,
-        None => return Err(Error::msg("no content delivered for download")),
-    };
-    let progress_bar = ProgressBar::new(content_length);
-    progress_bar.set_style(ProgressStyle::default_bar().template(DOWNLOAD_PROGRESS_BAR_TEMPLATE)?);
-    let mut client_file = File::create(client_file_path)?;
-    let mut hasher = Sha256::default();
-
-    progress_bar.set_message("Downloading...");
-    while let Some(chunk) = response.chunk().await? {
-        progress_bar.inc(chunk.len() as u64);
-        hasher.write_all(&chunk)?;
-        client_file.write_all(&chunk)?;
-    }
-
-    progress_bar.finish_with_message("Done");
-    let hash = hasher.finalize();
-    if hash[..] == project.application_download.sha256 {
-        println!("{} Download checksum correct!", CHECK_MARK.glyph);
-        Ok(())
-    } else {
-        println!("{} Download checksum does not match!", CROSS_MARK.glyph);
-        Err(Error::msg("download does not match hash"))
-    }
+    download_build(
+        &PaperMCDistributor {
+            project_name: &server_app.project.name,
+        },
+        &server_app.project.version,
+        &DistributedBuild {
+            number: server_app.build,
+            jar_name: server_app.application_download.name.clone(),
+            checksum: Checksum::Sha256(server_app.application_download.sha256.clone()),
+        },
+        client_file_path,
+        http_client,
+    )
+    .await
 }
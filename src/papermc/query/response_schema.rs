@@ -5,6 +5,20 @@ use serde::Deserialize;
 
 static PAPERMC_DOWNLOAD_TYPE_NAME: &str = "application";
 
+#[derive(Deserialize)]
+pub struct ProjectsResponse {
+    pub projects: Vec<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct ProjectResponse {
+    pub project_id: String,
+    pub project_name: String,
+    pub version_groups: Vec<String>,
+    pub versions: Vec<String>,
+}
+
 #[allow(dead_code)]
 #[derive(Deserialize)]
 pub struct VersionResponse {
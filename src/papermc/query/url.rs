@@ -1,13 +1,20 @@
 use crate::papermc::PaperMCProject;
-use crate::PaperMCServerApp;
-use crate::server::ServerApplication;
 
 static PAPERMC_API_BASE_URL: &str = "https://papermc.io/api/v2";
 
+pub fn papermc_projects_url() -> String {
+    format!("{}/projects", PAPERMC_API_BASE_URL)
+}
+
+pub fn papermc_project_url(project_name: &str) -> String {
+    format!("{}/{}", papermc_projects_url(), project_name)
+}
+
 pub fn papermc_project_version_url(project: &PaperMCProject) -> String {
     format!(
-        "{}/projects/{}/versions/{}",
-        PAPERMC_API_BASE_URL, project.name, project.version
+        "{}/versions/{}",
+        papermc_project_url(&project.name),
+        project.version
     )
 }
 
@@ -15,10 +22,17 @@ pub fn papermc_project_build_url(project: &PaperMCProject, build: i32) -> String
     format!("{}/builds/{}", papermc_project_version_url(project), build)
 }
 
-pub fn papermc_project_download_url(server_app: &PaperMCServerApp) -> String {
+pub fn papermc_project_download_url(
+    project_name: &str,
+    version: &str,
+    build: i32,
+    jar_name: &str,
+) -> String {
     format!(
-        "{}/downloads/{}",
-        papermc_project_build_url(&server_app.project, server_app.build),
-        server_app.application_name()
+        "{}/versions/{}/builds/{}/downloads/{}",
+        papermc_project_url(project_name),
+        version,
+        build,
+        jar_name
     )
 }
@@ -0,0 +1,183 @@
+use anyhow::Error;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
+use emoji::symbols::warning::WARNING;
+use reqwest::Client;
+
+use crate::config::{ServerType, Stainless};
+use crate::server::startup::load_startup_times;
+use crate::server::{acquire_server_app, save_server_info_if_exists, Server, ServerApplication};
+
+pub async fn check_servers(config: &Stainless, http_client: &Client) -> crate::Result<()> {
+    let mut failed_checks = 0;
+
+    for (name, server_type) in &config.servers {
+        let check_result = match server_type {
+            ServerType::PaperMC(server) => check_server(server, http_client).await,
+            ServerType::Vanilla(server) => check_server(server, http_client).await,
+            ServerType::Fabric(server) => check_server(server, http_client).await,
+            ServerType::Forge(server) => check_server(server, http_client).await,
+            ServerType::Fork(server) => check_server(server, http_client).await,
+            ServerType::Custom(server) => check_server(server, http_client).await,
+        };
+
+        if let Err(e) = check_result {
+            println!(
+                "{} Could not check {} for updates: {}",
+                CROSS_MARK.glyph, name, e
+            );
+            failed_checks += 1;
+        }
+    }
+
+    if failed_checks > 0 {
+        return Err(Error::msg(format!(
+            "{} server update check(s) failed",
+            failed_checks
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn update_servers(config: &Stainless, http_client: &Client) -> crate::Result<()> {
+    for server_type in config.servers.values() {
+        match server_type {
+            ServerType::PaperMC(server) => update_server(server, http_client).await,
+            ServerType::Vanilla(server) => update_server(server, http_client).await,
+            ServerType::Fabric(server) => update_server(server, http_client).await,
+            ServerType::Forge(server) => update_server(server, http_client).await,
+            ServerType::Fork(server) => update_server(server, http_client).await,
+            ServerType::Custom(server) => update_server(server, http_client).await,
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn show_server_statuses(
+    config: &Stainless,
+    http_client: &Client,
+    offline: bool,
+) -> crate::Result<()> {
+    for server_type in config.servers.values() {
+        match server_type {
+            ServerType::PaperMC(server) => show_server_status(server, http_client, offline).await,
+            ServerType::Vanilla(server) => show_server_status(server, http_client, offline).await,
+            ServerType::Fabric(server) => show_server_status(server, http_client, offline).await,
+            ServerType::Forge(server) => show_server_status(server, http_client, offline).await,
+            ServerType::Fork(server) => show_server_status(server, http_client, offline).await,
+            ServerType::Custom(server) => show_server_status(server, http_client, offline).await,
+        }
+    }
+
+    Ok(())
+}
+
+async fn check_server<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
+    http_client: &Client,
+) -> crate::Result<()> {
+    println!(
+        "{} Checking {} for updates...",
+        INFORMATION.glyph,
+        server.server_name()
+    );
+
+    let checking_server_app = match server.load_saved_server_app() {
+        Ok(server_app) => server_app,
+        Err(_) => {
+            println!(
+                "{} No server application is installed for {}.",
+                WARNING.glyph,
+                server.server_name()
+            );
+            server.default_version_check_client()
+        }
+    };
+
+    match checking_server_app
+        .check_for_updated_server(server, http_client)
+        .await?
+    {
+        Some(updated_server_app) => println!(
+            "{} {} can be updated to {}",
+            INFORMATION.glyph,
+            server.server_name(),
+            updated_server_app.application_name()
+        ),
+        None => println!(
+            "{} {} is up to date!",
+            CHECK_MARK.glyph,
+            server.server_name()
+        ),
+    }
+
+    Ok(())
+}
+
+async fn update_server<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
+    http_client: &Client,
+) {
+    println!("{} Updating {}...", INFORMATION.glyph, server.server_name());
+
+    let server_app = acquire_server_app(server, http_client, None).await;
+
+    match &server_app {
+        Some(server_app) => println!(
+            "{} {} is using {}!",
+            CHECK_MARK.glyph,
+            server.server_name(),
+            server_app.application_name()
+        ),
+        None => println!(
+            "{} No valid server could be acquired for {}!",
+            CROSS_MARK.glyph,
+            server.server_name()
+        ),
+    }
+
+    save_server_info_if_exists(server, &server_app);
+}
+
+async fn show_server_status<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
+    http_client: &Client,
+    offline: bool,
+) {
+    println!("{} Server {}", INFORMATION.glyph, server.server_name());
+
+    match server.load_saved_server_app() {
+        Ok(server_app) => {
+            println!("    Installed: {}", server_app);
+
+            if let Some(startup_times) =
+                load_startup_times(server.working_directory()).get(&server_app.build_name())
+            {
+                println!(
+                    "    Startup: {:.2}s last, {:.2}s average over {} start(s)",
+                    startup_times.last_seconds, startup_times.average_seconds, startup_times.starts
+                );
+            }
+        }
+        Err(e) => println!("    Installed: none ({})", e),
+    }
+    if let Ok(staged_server_app) = server.load_staged_server_app() {
+        println!("    Staged: {}", staged_server_app);
+    }
+
+    if offline {
+        return;
+    }
+
+    match server
+        .default_version_check_client()
+        .check_for_updated_server(server, http_client)
+        .await
+    {
+        Ok(Some(latest_server_app)) => println!("    Latest available: {}", latest_server_app),
+        Ok(None) => println!("    Latest available: none"),
+        Err(e) => println!("    Latest available: unknown ({})", e),
+    }
+}
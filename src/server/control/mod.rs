@@ -1,81 +1,270 @@
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use emoji::symbols::alphanum::INFORMATION;
 use emoji::symbols::other_symbol::CROSS_MARK;
 use emoji::symbols::punctuation::RED_QUESTION_MARK;
 use emoji::symbols::warning::WARNING;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::select;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::task::JoinHandle;
 
+use crate::server::control::stop::{is_stop_command, StopIntent, StopReason};
+use crate::server::logs::{LogEventSender, LOG_EVENT_CAPACITY};
+
+pub mod stop;
+
+static SERVER_ROUTING_PREFIX: char = '@';
+static STAINLESS_COMMAND_PREFIX: char = '!';
+
 pub struct ServerControl {
     pub control_thread: JoinHandle<crate::Result<()>>,
-    pub control_receiver: Receiver<u8>,
+    pub server_channels: HashMap<String, ServerChannels>,
+}
+
+/// Console input and log events of one server, and the shared record of why it was asked to
+/// stop.
+pub struct ServerChannels {
+    pub input_receiver: Receiver<String>,
+    pub input_sender: Sender<String>,
+    pub stop_intent: Arc<StopIntent>,
+    pub log_events: LogEventSender,
+    /// Whether console input has to be addressed to the server with `@<server>`, as it does when
+    /// several servers share the console.
+    pub addressed_input: bool,
+}
+
+struct ServerInput {
+    sender: Sender<String>,
+    stop_command: String,
+    stop_intent: Arc<StopIntent>,
 }
 
-pub async fn create_control_socket() -> crate::Result<ServerControl> {
-    let (tx, rx) = tokio::sync::mpsc::channel::<u8>(1024);
+/// Creates an input channel for each server, keyed by server name, along with the command that
+/// stops each server.
+pub async fn create_control_socket(
+    stop_commands: HashMap<String, String>,
+) -> crate::Result<ServerControl> {
+    let mut inputs = HashMap::new();
+    let mut server_channels = HashMap::new();
+    let addressed_input = stop_commands.len() > 1;
+
+    for (name, stop_command) in stop_commands {
+        let (tx, rx) = tokio::sync::mpsc::channel::<String>(64);
+        let stop_intent = Arc::new(StopIntent::default());
+
+        inputs.insert(
+            name.clone(),
+            ServerInput {
+                sender: tx.clone(),
+                stop_command,
+                stop_intent: stop_intent.clone(),
+            },
+        );
+        server_channels.insert(
+            name,
+            ServerChannels {
+                input_receiver: rx,
+                input_sender: tx,
+                stop_intent,
+                log_events: broadcast::channel(LOG_EVENT_CAPACITY).0,
+                addressed_input,
+            },
+        );
+    }
 
-    let control_task = tokio::spawn(write_input(tx));
+    let control_task = tokio::spawn(write_input(inputs));
 
     Ok(ServerControl {
         control_thread: control_task,
-        control_receiver: rx,
+        server_channels,
     })
 }
 
-async fn write_input(tx: Sender<u8>) -> crate::Result<()> {
-    let mut input = tokio::io::stdin();
+async fn write_input(inputs: HashMap<String, ServerInput>) -> crate::Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
 
-    loop {
-        let byte_read = input.read_u8().await?;
+    while let Some(line) = lines.next_line().await? {
+        if let Some(command) = line.strip_prefix(STAINLESS_COMMAND_PREFIX) {
+            run_stainless_command(&inputs, command).await;
+            continue;
+        }
+
+        match route_input_line(&inputs, &line) {
+            Some((input, routed_line)) => {
+                if is_stop_command(&routed_line, &input.stop_command) {
+                    input.stop_intent.record(StopReason::ConsoleCommand);
+                }
+
+                send_input(input, routed_line).await;
+            }
+            None => println!(
+                "{} Input must be addressed to a server, e.g. \"{}<server> <command>\". Servers: {}",
+                WARNING.glyph,
+                SERVER_ROUTING_PREFIX,
+                server_names(&inputs)
+            ),
+        }
+    }
+
+    // Servers keep running without a console, e.g. under a service manager. The senders are kept
+    // alive so that servers do not see their input channel break.
+    println!(
+        "{} Standard input closed; servers keep running without console input",
+        INFORMATION.glyph
+    );
+
+    std::future::pending().await
+}
+
+async fn send_input(input: &ServerInput, line: String) {
+    if input.sender.send(line).await.is_err() {
+        println!("{} Server is no longer accepting input.", WARNING.glyph);
+    }
+}
+
+fn route_input_line<'a>(
+    inputs: &'a HashMap<String, ServerInput>,
+    line: &str,
+) -> Option<(&'a ServerInput, String)> {
+    if let Some(addressed_line) = line.strip_prefix(SERVER_ROUTING_PREFIX) {
+        let (name, routed_line) = match addressed_line.split_once(' ') {
+            Some((name, routed_line)) => (name, routed_line),
+            None => (addressed_line, ""),
+        };
+
+        return inputs
+            .get(name)
+            .map(|input| (input, routed_line.to_string()));
+    }
 
-        tx.send(byte_read).await?;
+    if inputs.len() == 1 {
+        return inputs
+            .values()
+            .next()
+            .map(|input| (input, line.to_string()));
     }
+
+    None
 }
 
-pub async fn server_should_stop(socket: &mut Receiver<u8>) -> crate::Result<bool> {
+/// Runs a command addressed to Stainless itself rather than to a server, such as `!stop <server>`.
+async fn run_stainless_command(inputs: &HashMap<String, ServerInput>, command: &str) {
+    let mut words = command.split_whitespace();
+
+    match (words.next(), words.next()) {
+        (Some("stop"), server_name) => {
+            let target = match server_name {
+                Some(name) => inputs.get_key_value(name),
+                None if inputs.len() == 1 => inputs.iter().next(),
+                None => None,
+            };
+
+            match target {
+                Some((name, input)) => {
+                    println!("{} Stopping server {}...", INFORMATION.glyph, name);
+                    input.stop_intent.record(StopReason::StainlessRequest);
+                    send_input(input, input.stop_command.clone()).await;
+                }
+                None => println!(
+                    "{} Usage: {}stop <server>. Servers: {}",
+                    WARNING.glyph,
+                    STAINLESS_COMMAND_PREFIX,
+                    server_names(inputs)
+                ),
+            }
+        }
+        _ => println!(
+            "{} Unknown Stainless command. Available commands: {}stop <server>",
+            WARNING.glyph, STAINLESS_COMMAND_PREFIX
+        ),
+    }
+}
+
+fn server_names(inputs: &HashMap<String, ServerInput>) -> String {
+    let mut names: Vec<&str> = inputs.keys().map(String::as_str).collect();
+    names.sort_unstable();
+
+    names.join(", ")
+}
+
+pub fn is_interactive() -> bool {
+    atty::is(atty::Stream::Stdin)
+}
+
+/// Asks the operator whether to restart a server. An empty response picks `restart_by_default`.
+/// Returns `None` when nobody answers in time, leaving the decision to the restart policy. With
+/// `addressed_input`, the prompt tells the operator to address the answer to the server.
+pub async fn server_should_stop(
+    server_name: &str,
+    socket: &mut Receiver<String>,
+    restart_by_default: bool,
+    addressed_input: bool,
+) -> crate::Result<Option<bool>> {
     loop {
         let sleep = tokio::time::sleep(Duration::from_secs(5));
 
         select! {
-            result = should_restart_response(socket) => {
+            result = should_restart_response(
+                server_name,
+                socket,
+                restart_by_default,
+                addressed_input,
+            ) => {
                 match result {
-                    Ok(response) => if response.is_empty() || response == "y" || response == "yes" {
-                        return Ok(false)
+                    Ok(response) => if response.is_empty() {
+                        return Ok(Some(!restart_by_default))
+                    } else if response == "y" || response == "yes" {
+                        return Ok(Some(false))
                     } else if response == "n" || response == "no" {
-                        return Ok(true)
+                        return Ok(Some(true))
                     } else {
                         println!("{} Response invalid. Please try again...", WARNING.glyph)
                     },
                     Err(e) => {
                         println!("{} Error reading response: {}", CROSS_MARK.glyph, e);
-                        return Ok(false)
+                        return Ok(None)
                     }
                 }
             }
             _ = sleep => {
                 println!("{} No user response acquired in time", INFORMATION.glyph);
-                return Ok(false)
+                return Ok(None)
             }
         }
     }
 }
 
-async fn should_restart_response(socket: &mut Receiver<u8>) -> crate::Result<String> {
-    println!("{} Restart server? [Y/n]", RED_QUESTION_MARK.glyph);
-
-    let mut line = Vec::<u8>::new();
-
-    loop {
-        line.push(match socket.recv().await {
-            Some(byte) => byte,
-            None => return Err(anyhow::Error::msg("Input channel broke.")),
-        });
+async fn should_restart_response(
+    server_name: &str,
+    socket: &mut Receiver<String>,
+    restart_by_default: bool,
+    addressed_input: bool,
+) -> crate::Result<String> {
+    let choices = if restart_by_default { "[Y/n]" } else { "[y/N]" };
+    if addressed_input {
+        println!(
+            "{} Restart server {}? {} (answer with \"{}{} y\" or \"{}{} n\")",
+            RED_QUESTION_MARK.glyph,
+            server_name,
+            choices,
+            SERVER_ROUTING_PREFIX,
+            server_name,
+            SERVER_ROUTING_PREFIX,
+            server_name
+        );
+    } else {
+        println!(
+            "{} Restart server {}? {}",
+            RED_QUESTION_MARK.glyph, server_name, choices
+        );
+    }
 
-        if line.ends_with(&[b'\n']) {
-            return Ok(String::from_utf8(line)?.trim_end().to_string());
-        }
+    match socket.recv().await {
+        Some(line) => Ok(line.trim_end().to_string()),
+        None => Err(anyhow::Error::msg("Input channel broke.")),
     }
 }
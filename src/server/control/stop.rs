@@ -0,0 +1,84 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+
+#[derive(Clone, Copy)]
+pub enum StopReason {
+    ConsoleCommand,
+    StainlessRequest,
+    Signal,
+    ScheduledRestart,
+    Unresponsive,
+    UpdateStaged,
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopReason::ConsoleCommand => write!(f, "a stop command typed in the console"),
+            StopReason::StainlessRequest => write!(f, "a Stainless stop request"),
+            StopReason::Signal => write!(f, "a shutdown signal"),
+            StopReason::ScheduledRestart => write!(f, "a scheduled restart"),
+            StopReason::Unresponsive => write!(f, "failing its health checks"),
+            StopReason::UpdateStaged => write!(f, "a staged update"),
+        }
+    }
+}
+
+/// Why a server was asked to stop during its current run, if it was. Shared between the control
+/// thread that sees stop requests and the loop that runs the server.
+#[derive(Default)]
+pub struct StopIntent {
+    reason: Mutex<Option<StopReason>>,
+}
+
+impl StopIntent {
+    /// Records a stop request. The first request of a run is kept.
+    pub fn record(&self, reason: StopReason) {
+        let mut recorded_reason = self.reason.lock().unwrap();
+        if recorded_reason.is_none() {
+            *recorded_reason = Some(reason);
+        }
+    }
+
+    pub fn is_recorded(&self) -> bool {
+        self.reason.lock().unwrap().is_some()
+    }
+
+    pub fn take(&self) -> Option<StopReason> {
+        self.reason.lock().unwrap().take()
+    }
+}
+
+/// Whether a console line is the server's stop command, ignoring case, a leading `/` and the
+/// `minecraft:` namespace.
+pub fn is_stop_command(line: &str, stop_command: &str) -> bool {
+    let command = line.trim().trim_start_matches('/');
+    let command = match command.get(..10) {
+        Some(namespace) if namespace.eq_ignore_ascii_case("minecraft:") => &command[10..],
+        _ => command,
+    };
+
+    command.eq_ignore_ascii_case(stop_command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_the_stop_command_with_or_without_a_slash() {
+        assert!(is_stop_command("stop", "stop"));
+        assert!(is_stop_command("/stop", "stop"));
+        assert!(is_stop_command("  STOP \n", "stop"));
+        assert!(is_stop_command("/minecraft:stop", "stop"));
+        assert!(is_stop_command("/end", "end"));
+    }
+
+    #[test]
+    fn ignores_other_commands() {
+        assert!(!is_stop_command("stop now", "stop"));
+        assert!(!is_stop_command("/stopwatch", "stop"));
+        assert!(!is_stop_command("say stop", "stop"));
+        assert!(!is_stop_command("/end", "stop"));
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use async_trait::async_trait;
+use md5::Md5;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::server::download::download_verified_file;
+
+/// A source of server jars that numbers the builds of each Minecraft version and publishes them,
+/// with a checksum, through an API of its own. PaperMC's projects and forks of Paper such as Purpur
+/// are distributed this way, each with a differently shaped API.
+#[async_trait]
+pub trait BuildDistributor: Sync {
+    /// Name of the distributor, such as `PaperMC` or `Purpur`.
+    fn name(&self) -> &str;
+
+    /// Minecraft versions builds are published for.
+    async fn versions(&self, http_client: &Client) -> crate::Result<Vec<String>>;
+
+    /// Newest build published for a Minecraft version.
+    async fn latest_build(
+        &self,
+        version: &str,
+        http_client: &Client,
+    ) -> crate::Result<DistributedBuild>;
+
+    /// URL the jar of a build of a Minecraft version is downloaded from.
+    fn download_url(&self, version: &str, build: &DistributedBuild) -> String;
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DistributedBuild {
+    pub number: i32,
+    /// File name of the build's jar, such as `purpur-1.18.2-1632.jar`.
+    pub jar_name: String,
+    pub checksum: Checksum,
+}
+
+impl Display for DistributedBuild {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{Build: {}, Name: {}, {}}}",
+            self.number, self.jar_name, self.checksum
+        )
+    }
+}
+
+/// Hash a distributor publishes for the jar of a build.
+#[derive(Serialize, Deserialize, Clone)]
+pub enum Checksum {
+    Md5(Vec<u8>),
+    Sha256(Vec<u8>),
+}
+
+impl Display for Checksum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Checksum::Md5(hash) => write!(f, "MD5: {}", hex::encode(hash)),
+            Checksum::Sha256(hash) => write!(f, "SHA256: {}", hex::encode(hash)),
+        }
+    }
+}
+
+/// Downloads the jar of a build, checking it against the checksum the distributor published.
+pub async fn download_build(
+    distributor: &dyn BuildDistributor,
+    version: &str,
+    build: &DistributedBuild,
+    file_path: &Path,
+    http_client: &Client,
+) -> crate::Result<()> {
+    let url = distributor.download_url(version, build);
+
+    match &build.checksum {
+        Checksum::Md5(hash) => {
+            download_verified_file::<Md5>(&url, file_path, hash, http_client).await
+        }
+        Checksum::Sha256(hash) => {
+            download_verified_file::<Sha256>(&url, file_path, hash, http_client).await
+        }
+    }
+}
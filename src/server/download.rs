@@ -0,0 +1,98 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Error;
+use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::Client;
+use sha2::Digest;
+
+use crate::config::constants::DOWNLOAD_PROGRESS_BAR_TEMPLATE;
+
+/// Downloads a file with a progress bar, hashing it with `D` as it arrives. Fails when the hash
+/// does not match `expected_hash`, leaving any existing file at `file_path` untouched.
+pub async fn download_verified_file<D: Digest + Write>(
+    url: &str,
+    file_path: &Path,
+    expected_hash: &[u8],
+    http_client: &Client,
+) -> crate::Result<()> {
+    let partial_file_path = partial_file_path(file_path);
+    let hash = download_partial_file::<D>(url, &partial_file_path, http_client).await?;
+
+    if hash == expected_hash {
+        println!("{} Download checksum correct!", CHECK_MARK.glyph);
+        fs::rename(&partial_file_path, file_path)?;
+        Ok(())
+    } else {
+        println!("{} Download checksum does not match!", CROSS_MARK.glyph);
+        let _ = fs::remove_file(&partial_file_path);
+        Err(Error::msg("download does not match hash"))
+    }
+}
+
+/// Downloads a file with a progress bar, returning its hash computed with `D` as it arrived.
+pub async fn download_file<D: Digest + Write>(
+    url: &str,
+    file_path: &Path,
+    http_client: &Client,
+) -> crate::Result<Vec<u8>> {
+    let partial_file_path = partial_file_path(file_path);
+    let hash = download_partial_file::<D>(url, &partial_file_path, http_client).await?;
+    fs::rename(&partial_file_path, file_path)?;
+
+    Ok(hash)
+}
+
+/// File a download is written to until it is complete, next to its destination so that it can be
+/// renamed over it.
+fn partial_file_path(file_path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(file_path.file_name().unwrap_or_default());
+    file_name.push(".part");
+
+    file_path.with_file_name(file_name)
+}
+
+async fn download_partial_file<D: Digest + Write>(
+    url: &str,
+    partial_file_path: &Path,
+    http_client: &Client,
+) -> crate::Result<Vec<u8>> {
+    let result = write_download::<D>(url, partial_file_path, http_client).await;
+    if result.is_err() {
+        let _ = fs::remove_file(partial_file_path);
+    }
+
+    result
+}
+
+async fn write_download<D: Digest + Write>(
+    url: &str,
+    file_path: &Path,
+    http_client: &Client,
+) -> crate::Result<Vec<u8>> {
+    let mut response = http_client.get(url).send().await?.error_for_status()?;
+
+    let content_length = match response.content_length() {
+        Some(len) => len,
+        None => return Err(Error::msg("no content delivered for download")),
+    };
+    let progress_bar = ProgressBar::new(content_length);
+    progress_bar.set_style(ProgressStyle::default_bar().template(DOWNLOAD_PROGRESS_BAR_TEMPLATE)?);
+    let mut file = File::create(file_path)?;
+    let mut hasher = D::new();
+
+    progress_bar.set_message("Downloading...");
+    while let Some(chunk) = response.chunk().await? {
+        progress_bar.inc(chunk.len() as u64);
+        hasher.write_all(&chunk)?;
+        file.write_all(&chunk)?;
+    }
+
+    progress_bar.finish_with_message("Done");
+
+    Ok(hasher.finalize().to_vec())
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
+use emoji::symbols::warning::WARNING;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Child;
+
+use crate::server::health::ping::query_server_status;
+use crate::server::state::{wait_until_ready, ServerStateReceiver};
+
+pub mod ping;
+
+static DEFAULT_SERVER_PORT: u16 = 25565;
+static SERVER_PROPERTIES_FILE_NAME: &str = "server.properties";
+static SERVER_PORT_PROPERTY: &str = "server-port";
+
+/// Checks that a running server answers status requests, the way the multiplayer server list
+/// does. A server that stops answering is restarted.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthCheck {
+    /// Address the server is reached at.
+    pub address: String,
+    /// Port the server listens on. Defaults to `server-port` in the server's `server.properties`,
+    /// or 25565.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Longest time, in seconds, to wait for the server to finish starting before the first check.
+    pub startup_grace_seconds: u64,
+    /// Seconds between checks.
+    pub interval_seconds: u64,
+    /// Seconds to wait for the server to answer a check.
+    pub timeout_seconds: u64,
+    /// Consecutive failed checks after which the server is restarted.
+    pub failure_threshold: u32,
+    /// Whether to take a thread dump of an unresponsive server before restarting it.
+    pub thread_dump: bool,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        HealthCheck {
+            address: String::from("127.0.0.1"),
+            port: None,
+            startup_grace_seconds: 120,
+            interval_seconds: 30,
+            timeout_seconds: 5,
+            failure_threshold: 3,
+            thread_dump: true,
+        }
+    }
+}
+
+/// Polls a server's status once it is ready until it fails `failure_threshold` checks in a row,
+/// and then returns. Never returns when the server has no health check.
+pub async fn wait_until_unhealthy(
+    server_name: &str,
+    working_directory: &Path,
+    health_check: Option<&HealthCheck>,
+    mut state: ServerStateReceiver,
+) {
+    let health_check = match health_check {
+        Some(health_check) => health_check,
+        None => return std::future::pending().await,
+    };
+    let check_timeout = Duration::from_secs(health_check.timeout_seconds);
+
+    let startup_grace = Duration::from_secs(health_check.startup_grace_seconds);
+    if tokio::time::timeout(startup_grace, wait_until_ready(&mut state))
+        .await
+        .is_err()
+    {
+        println!(
+            "{} Server {} has not finished starting after {} seconds; checking its health anyway",
+            WARNING.glyph, server_name, health_check.startup_grace_seconds
+        );
+    }
+
+    // A new server writes its server.properties while starting, so the port is read afterwards.
+    let port = match health_check.port {
+        Some(port) => port,
+        None => configured_server_port(working_directory),
+    };
+
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(health_check.interval_seconds.max(1)));
+    let mut failed_checks = 0;
+
+    loop {
+        interval.tick().await;
+
+        let check_result = match tokio::time::timeout(
+            check_timeout,
+            query_server_status(&health_check.address, port),
+        )
+        .await
+        {
+            Ok(status_result) => status_result,
+            Err(_) => Err(anyhow::Error::msg(format!(
+                "no answer within {} seconds",
+                health_check.timeout_seconds
+            ))),
+        };
+
+        match check_result {
+            Ok(status) => {
+                if failed_checks > 0 {
+                    println!(
+                        "{} Server {} is answering again ({}, {}/{} players, {} ms)",
+                        CHECK_MARK.glyph,
+                        server_name,
+                        status.version.name,
+                        status.players.online,
+                        status.players.max,
+                        status.latency.as_millis()
+                    );
+                }
+                failed_checks = 0;
+            }
+            Err(e) => {
+                failed_checks += 1;
+                println!(
+                    "{} Health check {}/{} of {} at {}:{} failed: {}",
+                    WARNING.glyph,
+                    failed_checks,
+                    health_check.failure_threshold,
+                    server_name,
+                    health_check.address,
+                    port,
+                    e
+                );
+
+                if failed_checks >= health_check.failure_threshold {
+                    println!(
+                        "{} Server {} is unresponsive; restarting it...",
+                        CROSS_MARK.glyph, server_name
+                    );
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Asks the JVM to print a thread dump to its console output, showing where an unresponsive
+/// server is stuck.
+#[cfg(unix)]
+pub async fn request_thread_dump(server_name: &str, server_process: &Child) {
+    let process_id = match server_process.id() {
+        Some(process_id) => process_id,
+        None => return,
+    };
+
+    println!(
+        "{} Requesting a thread dump of {}...",
+        INFORMATION.glyph, server_name
+    );
+
+    unsafe {
+        libc::kill(process_id as libc::pid_t, libc::SIGQUIT);
+    }
+
+    // Give the JVM a moment to write the dump before the server is stopped.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+}
+
+#[cfg(not(unix))]
+pub async fn request_thread_dump(server_name: &str, _server_process: &Child) {
+    println!(
+        "{} Thread dumps of {} are only taken on Unix",
+        WARNING.glyph, server_name
+    );
+}
+
+fn configured_server_port(working_directory: &Path) -> u16 {
+    let properties = match fs::read_to_string(working_directory.join(SERVER_PROPERTIES_FILE_NAME)) {
+        Ok(properties) => properties,
+        Err(_) => return DEFAULT_SERVER_PORT,
+    };
+
+    properties
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == SERVER_PORT_PROPERTY)
+        .and_then(|(_, port)| port.trim().parse().ok())
+        .unwrap_or(DEFAULT_SERVER_PORT)
+}
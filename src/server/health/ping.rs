@@ -0,0 +1,181 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Error;
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+static HANDSHAKE_PACKET_ID: i32 = 0x00;
+static STATUS_PACKET_ID: i32 = 0x00;
+static STATUS_NEXT_STATE: i32 = 1;
+/// Servers answer status requests whatever protocol version the client claims; -1 is the
+/// customary version for clients that only ping.
+static PING_PROTOCOL_VERSION: i32 = -1;
+static MAX_RESPONSE_LENGTH: i32 = 1 << 21;
+
+/// Status a server reports through the Server List Ping protocol.
+#[derive(Deserialize)]
+pub struct ServerStatus {
+    pub version: StatusVersion,
+    pub players: StatusPlayers,
+    #[serde(skip)]
+    pub latency: Duration,
+}
+
+#[derive(Deserialize)]
+pub struct StatusVersion {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct StatusPlayers {
+    pub online: i64,
+    pub max: i64,
+}
+
+/// Queries a server's status with the Server List Ping protocol: a handshake into the status
+/// state followed by a status request, answered with a JSON document.
+pub async fn query_server_status(address: &str, port: u16) -> crate::Result<ServerStatus> {
+    let started = Instant::now();
+    let mut stream = TcpStream::connect((address, port)).await?;
+
+    let mut handshake = vec![];
+    write_var_int(&mut handshake, HANDSHAKE_PACKET_ID);
+    write_var_int(&mut handshake, PING_PROTOCOL_VERSION);
+    write_string(&mut handshake, address);
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_var_int(&mut handshake, STATUS_NEXT_STATE);
+    write_packet(&mut stream, &handshake).await?;
+
+    let mut status_request = vec![];
+    write_var_int(&mut status_request, STATUS_PACKET_ID);
+    write_packet(&mut stream, &status_request).await?;
+
+    let response_length = read_var_int(&mut stream).await?;
+    if !(1..=MAX_RESPONSE_LENGTH).contains(&response_length) {
+        return Err(Error::msg(format!(
+            "server sent a status response of invalid length {}",
+            response_length
+        )));
+    }
+
+    let mut response = vec![0; response_length as usize];
+    stream.read_exact(&mut response).await?;
+    let mut response = response.as_slice();
+
+    let packet_id = read_var_int(&mut response).await?;
+    if packet_id != STATUS_PACKET_ID {
+        return Err(Error::msg(format!(
+            "server answered the status request with packet {:#04x}",
+            packet_id
+        )));
+    }
+
+    let json_length = read_var_int(&mut response).await?;
+    if json_length < 0 || json_length as usize > response.len() {
+        return Err(Error::msg("server sent a malformed status response"));
+    }
+
+    let mut status: ServerStatus = serde_json::from_slice(&response[..json_length as usize])?;
+    status.latency = started.elapsed();
+
+    Ok(status)
+}
+
+async fn write_packet(stream: &mut TcpStream, packet: &[u8]) -> crate::Result<()> {
+    let mut framed_packet = vec![];
+    write_var_int(&mut framed_packet, packet.len() as i32);
+    framed_packet.extend_from_slice(packet);
+
+    stream.write_all(&framed_packet).await?;
+
+    Ok(())
+}
+
+fn write_var_int(buffer: &mut Vec<u8>, value: i32) {
+    let mut value = value as u32;
+
+    loop {
+        if value & !0x7f == 0 {
+            buffer.push(value as u8);
+            return;
+        }
+
+        buffer.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    write_var_int(buffer, value.len() as i32);
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+async fn read_var_int<R: AsyncReadExt + Unpin>(reader: &mut R) -> crate::Result<i32> {
+    let mut value: u32 = 0;
+
+    for position in 0..5 {
+        let byte = reader.read_u8().await?;
+        value |= ((byte & 0x7f) as u32) << (7 * position);
+
+        if byte & 0x80 == 0 {
+            return Ok(value as i32);
+        }
+    }
+
+    Err(Error::msg("server sent a VarInt longer than 5 bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_var_int(value: i32) -> Vec<u8> {
+        let mut buffer = vec![];
+        write_var_int(&mut buffer, value);
+
+        buffer
+    }
+
+    #[test]
+    fn encodes_var_ints() {
+        assert_eq!(encode_var_int(0), vec![0x00]);
+        assert_eq!(encode_var_int(127), vec![0x7f]);
+        assert_eq!(encode_var_int(128), vec![0x80, 0x01]);
+        assert_eq!(encode_var_int(25565), vec![0xdd, 0xc7, 0x01]);
+        assert_eq!(encode_var_int(i32::MAX), vec![0xff, 0xff, 0xff, 0xff, 0x07]);
+        assert_eq!(encode_var_int(-1), vec![0xff, 0xff, 0xff, 0xff, 0x0f]);
+    }
+
+    #[tokio::test]
+    async fn decodes_what_it_encodes() {
+        for value in [0, 1, 127, 128, 255, 25565, 2097151, i32::MAX, -1, i32::MIN] {
+            let encoded = encode_var_int(value);
+
+            assert_eq!(read_var_int(&mut encoded.as_slice()).await.unwrap(), value);
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_var_ints_longer_than_five_bytes() {
+        let encoded = [0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+
+        assert!(read_var_int(&mut encoded.as_slice()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_truncated_var_ints() {
+        let encoded = [0xff, 0xff];
+
+        assert!(read_var_int(&mut encoded.as_slice()).await.is_err());
+    }
+
+    #[test]
+    fn prefixes_strings_with_their_length() {
+        let mut buffer = vec![];
+        write_string(&mut buffer, "localhost");
+
+        assert_eq!(buffer[0], 9);
+        assert_eq!(&buffer[1..], b"localhost");
+    }
+}
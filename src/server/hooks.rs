@@ -0,0 +1,196 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Error;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::warning::WARNING;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::server::{Server, ServerApplication};
+
+/// Shell commands run around a server's lifecycle. Each hook is a list of commands run in order
+/// from the server's working directory, stopping at the first one that fails.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    /// Seconds a hook command may run before it is killed and counted as failed.
+    pub timeout_seconds: u64,
+    /// Run before a newer build of the server is downloaded, or before it is swapped in when it was
+    /// staged in the background.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pre_update: Vec<String>,
+    /// Run after a newer build of the server was downloaded or swapped in.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post_update: Vec<String>,
+    /// Run before the server starts. A failing command cancels the start, which then counts as a
+    /// failed run under the restart policy.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pre_start: Vec<String>,
+    /// Run once the server has finished starting.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post_start: Vec<String>,
+    /// Run after the server exits, however it exited.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post_stop: Vec<String>,
+    /// Run after the server exits with a failure.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub on_crash: Vec<String>,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Hooks {
+            timeout_seconds: 60,
+            pre_update: vec![],
+            post_update: vec![],
+            pre_start: vec![],
+            post_start: vec![],
+            post_stop: vec![],
+            on_crash: vec![],
+        }
+    }
+}
+
+impl Hooks {
+    fn commands(&self, hook: Hook) -> &[String] {
+        match hook {
+            Hook::PreUpdate => &self.pre_update,
+            Hook::PostUpdate => &self.post_update,
+            Hook::PreStart => &self.pre_start,
+            Hook::PostStart => &self.post_start,
+            Hook::PostStop => &self.post_stop,
+            Hook::OnCrash => &self.on_crash,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Hook {
+    PreUpdate,
+    PostUpdate,
+    PreStart,
+    PostStart,
+    PostStop,
+    OnCrash,
+}
+
+impl Display for Hook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hook::PreUpdate => write!(f, "pre_update"),
+            Hook::PostUpdate => write!(f, "post_update"),
+            Hook::PreStart => write!(f, "pre_start"),
+            Hook::PostStart => write!(f, "post_start"),
+            Hook::PostStop => write!(f, "post_stop"),
+            Hook::OnCrash => write!(f, "on_crash"),
+        }
+    }
+}
+
+/// What a hook is told about the server it runs for, passed to its commands as `STAINLESS_*`
+/// environment variables.
+#[derive(Clone)]
+pub struct HookContext {
+    server_name: String,
+    working_directory: PathBuf,
+    variables: Vec<(&'static str, String)>,
+}
+
+impl HookContext {
+    pub fn new<S: Server<S, A>, A: ServerApplication<S, A>>(server: &S) -> HookContext {
+        HookContext {
+            server_name: server.server_name().to_string(),
+            working_directory: server.working_directory().to_path_buf(),
+            variables: vec![
+                ("STAINLESS_SERVER_NAME", server.server_name().to_string()),
+                (
+                    "STAINLESS_SERVER_DIRECTORY",
+                    server.working_directory().display().to_string(),
+                ),
+            ],
+        }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl ToString) -> HookContext {
+        self.variables.push((name, value.to_string()));
+        self
+    }
+}
+
+/// Runs a hook's commands in order, failing on the first command that fails or times out.
+pub async fn run_hook(hooks: &Hooks, hook: Hook, context: &HookContext) -> crate::Result<()> {
+    for command in hooks.commands(hook) {
+        println!(
+            "{} Running {} hook of {}: {}",
+            INFORMATION.glyph, hook, context.server_name, command
+        );
+
+        run_hook_command(command, hook, context, hooks.timeout_seconds)
+            .await
+            .map_err(|e| Error::msg(format!("{} hook `{}` failed: {}", hook, command, e)))?;
+    }
+
+    Ok(())
+}
+
+/// Runs a hook whose failure does not change what happens to the server, only reporting it.
+pub async fn run_hook_reporting_failure(hooks: &Hooks, hook: Hook, context: &HookContext) {
+    if let Err(e) = run_hook(hooks, hook, context).await {
+        println!(
+            "{} Hook of {} failed: {}",
+            WARNING.glyph, context.server_name, e
+        );
+    }
+}
+
+async fn run_hook_command(
+    command: &str,
+    hook: Hook,
+    context: &HookContext,
+    timeout_seconds: u64,
+) -> crate::Result<()> {
+    let mut hook_process = shell_command(command)
+        .current_dir(&context.working_directory)
+        .env("STAINLESS_HOOK", hook.to_string())
+        .envs(context.variables.iter().map(|(name, value)| (name, value)))
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let exit_status =
+        match tokio::time::timeout(Duration::from_secs(timeout_seconds), hook_process.wait()).await
+        {
+            Ok(exit_status) => exit_status?,
+            Err(_) => {
+                hook_process.kill().await?;
+                return Err(Error::msg(format!(
+                    "timed out after {} seconds",
+                    timeout_seconds
+                )));
+            }
+        };
+
+    if exit_status.success() {
+        Ok(())
+    } else {
+        Err(Error::msg(format!("exited with {}", exit_status)))
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut shell_command = Command::new("sh");
+    shell_command.arg("-c").arg(command);
+    shell_command
+}
+
+#[cfg(not(unix))]
+fn shell_command(command: &str) -> Command {
+    let mut shell_command = Command::new("cmd");
+    shell_command.arg("/C").arg(command);
+    shell_command
+}
@@ -0,0 +1,352 @@
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use crate::server::startup::parse_ready_line;
+
+/// Events buffered for each subscriber before the slowest one starts missing events.
+pub static LOG_EVENT_CAPACITY: usize = 256;
+
+static JOIN_SUFFIX: &str = " joined the game";
+static LEAVE_SUFFIX: &str = " left the game";
+static UNSIGNED_CHAT_PREFIX: &str = "[Not Secure] ";
+static LAG_MARKER: &str = "Can't keep up!";
+static PLUGIN_ENABLE_FAILURE_PREFIX: &str = "Error occurred while enabling ";
+static PLUGIN_ENABLE_FAILURE_SUFFIX: &str = " (Is it up to date?)";
+
+/// Something a server reported in its console output.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogEvent {
+    /// The server finished starting, taking `startup` to do so.
+    Ready {
+        startup: Duration,
+    },
+    PlayerJoined {
+        player: String,
+    },
+    PlayerLeft {
+        player: String,
+    },
+    Chat {
+        player: String,
+        message: String,
+    },
+    /// The server fell behind on ticks, usually because it is overloaded.
+    CantKeepUp {
+        behind: Duration,
+        ticks: u64,
+    },
+    PluginEnableFailed {
+        plugin: String,
+    },
+    /// An exception the server logged, with the lines of its stack trace.
+    Exception {
+        exception: String,
+        stack_trace: Vec<String>,
+    },
+}
+
+pub type LogEventSender = broadcast::Sender<LogEvent>;
+pub type LogEventReceiver = broadcast::Receiver<LogEvent>;
+
+/// Turns console output into log events, one line at a time. Stack traces span several lines, so
+/// an exception is reported once the line after its stack trace arrives.
+#[derive(Default)]
+pub struct LogParser {
+    exception: Option<(String, Vec<String>)>,
+}
+
+impl LogParser {
+    pub fn parse_line(&mut self, line: &str) -> Vec<LogEvent> {
+        let line = strip_ansi_codes(line);
+        let message = log_message(&line);
+        let mut events = vec![];
+
+        if let Some((_, stack_trace)) = &mut self.exception {
+            if is_stack_trace_line(message) {
+                stack_trace.push(message.trim().to_string());
+                return events;
+            }
+        }
+        events.extend(self.finish());
+
+        if is_exception_line(message) {
+            self.exception = Some((message.trim().to_string(), vec![]));
+        } else if let Some(event) = parse_message(message) {
+            events.push(event);
+        }
+
+        events
+    }
+
+    /// Reports the exception still being read, if there is one.
+    pub fn finish(&mut self) -> Option<LogEvent> {
+        self.exception
+            .take()
+            .map(|(exception, stack_trace)| LogEvent::Exception {
+                exception,
+                stack_trace,
+            })
+    }
+}
+
+/// Strips the timestamp, thread and level from a log line, handling both the vanilla format
+/// (`[12:00:00] [Server thread/INFO]: message`) and Paper's (`[12:00:00 INFO]: message`). Lines
+/// without a prefix, such as stack traces, are returned as they are.
+fn log_message(line: &str) -> &str {
+    if !line.starts_with('[') {
+        return line;
+    }
+
+    match line.find("]: ") {
+        Some(prefix_end) => &line[prefix_end + 3..],
+        None => line,
+    }
+}
+
+fn parse_message(message: &str) -> Option<LogEvent> {
+    let chat = message
+        .strip_prefix(UNSIGNED_CHAT_PREFIX)
+        .unwrap_or(message);
+    if let Some(chat) = chat.strip_prefix('<') {
+        let (player, text) = chat.split_once("> ")?;
+        if is_player_name(player) {
+            return Some(LogEvent::Chat {
+                player: player.to_string(),
+                message: text.to_string(),
+            });
+        }
+    }
+
+    // Checked after chat, so that a player cannot announce the server as ready.
+    if let Some(startup) = parse_ready_line(message) {
+        return Some(LogEvent::Ready { startup });
+    }
+
+    if let Some(player) = message.strip_suffix(JOIN_SUFFIX) {
+        if is_player_name(player) {
+            return Some(LogEvent::PlayerJoined {
+                player: player.to_string(),
+            });
+        }
+    }
+    if let Some(player) = message.strip_suffix(LEAVE_SUFFIX) {
+        if is_player_name(player) {
+            return Some(LogEvent::PlayerLeft {
+                player: player.to_string(),
+            });
+        }
+    }
+
+    if message.contains(LAG_MARKER) {
+        return parse_lag_warning(message);
+    }
+
+    if let Some(plugin) = message.strip_prefix(PLUGIN_ENABLE_FAILURE_PREFIX) {
+        return Some(LogEvent::PluginEnableFailed {
+            plugin: plugin
+                .strip_suffix(PLUGIN_ENABLE_FAILURE_SUFFIX)
+                .unwrap_or(plugin)
+                .to_string(),
+        });
+    }
+
+    None
+}
+
+/// Reads `Running 2345ms or 46 ticks behind` from a lag warning.
+fn parse_lag_warning(message: &str) -> Option<LogEvent> {
+    let words: Vec<&str> = message.split_whitespace().collect();
+    let behind = words
+        .iter()
+        .find_map(|word| word.strip_suffix("ms")?.parse().ok())?;
+    let ticks = words
+        .windows(2)
+        .find(|pair| pair[1] == "ticks")
+        .and_then(|pair| pair[0].parse().ok())?;
+
+    Some(LogEvent::CantKeepUp {
+        behind: Duration::from_millis(behind),
+        ticks,
+    })
+}
+
+/// Player names are a single word; Bedrock players joining through a proxy may carry a prefix
+/// such as `.`, so only whitespace and chat punctuation are ruled out.
+fn is_player_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && !name.contains(|c: char| c.is_whitespace() || c == '<' || c == '>' || c == ':')
+}
+
+/// Whether a line starts an exception, such as `java.lang.IllegalStateException: message`.
+fn is_exception_line(message: &str) -> bool {
+    let class_name = message
+        .trim()
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .next()
+        .unwrap_or_default();
+    let simple_name = match class_name.rsplit_once('.') {
+        Some((package, simple_name)) if !package.is_empty() => simple_name,
+        _ => return false,
+    };
+
+    class_name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '.' || c == '_' || c == '$')
+        && (simple_name.ends_with("Exception")
+            || simple_name.ends_with("Error")
+            || simple_name.ends_with("Throwable"))
+}
+
+fn is_stack_trace_line(message: &str) -> bool {
+    let message = message.trim_start();
+
+    message.starts_with("at ")
+        || message.starts_with("Caused by: ")
+        || message.starts_with("Suppressed: ")
+        || (message.starts_with("... ") && message.ends_with(" more"))
+}
+
+fn strip_ansi_codes(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip the escape sequence up to and including its final letter.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(lines: &[&str]) -> Vec<LogEvent> {
+        let mut parser = LogParser::default();
+        let mut events: Vec<LogEvent> = lines
+            .iter()
+            .flat_map(|line| parser.parse_line(line))
+            .collect();
+        events.extend(parser.finish());
+
+        events
+    }
+
+    #[test]
+    fn parses_vanilla_and_paper_formats() {
+        let joined = LogEvent::PlayerJoined {
+            player: String::from("Steve"),
+        };
+
+        assert_eq!(
+            parse(&["[12:00:00] [Server thread/INFO]: Steve joined the game"]),
+            vec![joined.clone()]
+        );
+        assert_eq!(
+            parse(&["[12:00:00 INFO]: Steve joined the game"]),
+            vec![joined]
+        );
+    }
+
+    #[test]
+    fn parses_ready_line() {
+        assert_eq!(
+            parse(&["[12:00:00 INFO]: Done (1.234s)! For help, type \"help\""]),
+            vec![LogEvent::Ready {
+                startup: Duration::from_millis(1234)
+            }]
+        );
+    }
+
+    #[test]
+    fn chat_cannot_announce_the_server_as_ready() {
+        assert_eq!(
+            parse(&["[12:00:00 INFO]: <Steve> Done (1.234s)! For help, type \"help\""]),
+            vec![LogEvent::Chat {
+                player: String::from("Steve"),
+                message: String::from("Done (1.234s)! For help, type \"help\""),
+            }]
+        );
+        assert_eq!(
+            parse(&["[12:00:00 INFO]: [Not Secure] <Steve> Steve joined the game"]),
+            vec![LogEvent::Chat {
+                player: String::from("Steve"),
+                message: String::from("Steve joined the game"),
+            }]
+        );
+    }
+
+    #[test]
+    fn ignores_sentences_that_only_look_like_joins() {
+        assert_eq!(
+            parse(&["[12:00:00 INFO]: A player joined the game"]),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn parses_lag_warnings_and_plugin_failures() {
+        assert_eq!(
+            parse(&[
+                "[12:00:00 WARN]: Can't keep up! Is the server overloaded? Running 2345ms or 46 ticks behind",
+                "[12:00:00 ERROR]: Error occurred while enabling Essentials v2.19.0 (Is it up to date?)",
+            ]),
+            vec![
+                LogEvent::CantKeepUp {
+                    behind: Duration::from_millis(2345),
+                    ticks: 46,
+                },
+                LogEvent::PluginEnableFailed {
+                    plugin: String::from("Essentials v2.19.0"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn collects_stack_traces() {
+        assert_eq!(
+            parse(&[
+                "[12:00:00 ERROR]: java.lang.IllegalStateException: broken",
+                "\tat com.example.Plugin.onEnable(Plugin.java:10)",
+                "Caused by: java.lang.NullPointerException",
+                "\t... 3 more",
+                "[12:00:01 INFO]: Steve left the game",
+            ]),
+            vec![
+                LogEvent::Exception {
+                    exception: String::from("java.lang.IllegalStateException: broken"),
+                    stack_trace: vec![
+                        String::from("at com.example.Plugin.onEnable(Plugin.java:10)"),
+                        String::from("Caused by: java.lang.NullPointerException"),
+                        String::from("... 3 more"),
+                    ],
+                },
+                LogEvent::PlayerLeft {
+                    player: String::from("Steve"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_ansi_codes() {
+        assert_eq!(
+            parse(&["\u{1b}[32m[12:00:00 INFO]: Steve joined the game\u{1b}[0m"]),
+            vec![LogEvent::PlayerJoined {
+                player: String::from("Steve"),
+            }]
+        );
+    }
+}
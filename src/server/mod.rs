@@ -1,52 +1,260 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 use std::process::ExitStatus;
+use std::time::Duration;
 
 use anyhow::Error;
 use async_trait::async_trait;
 use emoji::symbols::alphanum::INFORMATION;
 use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
 use reqwest::Client;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use tokio::select;
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
 
+use crate::config::constants::SERVER_INFO_FILE_NAME;
+use crate::config::reload::ConfigUpdates;
 use crate::config::ServerType;
 use crate::server::control::create_control_socket;
+use crate::server::control::stop::StopReason;
+use crate::server::control::ServerChannels;
+use crate::server::health::HealthCheck;
+use crate::server::hooks::{run_hook, run_hook_reporting_failure, Hook, HookContext, Hooks};
+use crate::server::process::ProcessChannels;
+use crate::server::restart::{RestartDecision, RestartPolicy, RestartTracker, RunOutcome};
+use crate::server::schedule::run_restart_schedule;
+use crate::server::shutdown::{listen_for_shutdown_signals, ShutdownReceiver, ShutdownTimeouts};
+use crate::server::state::{
+    write_state_transitions, ServerLifecycle, ServerState, StateTransitionSender,
+    STATE_TRANSITION_CAPACITY,
+};
+use crate::server::updates::{
+    promote_staged_server_app, run_background_updates, staged_server_directory, BackgroundUpdates,
+};
 
-mod control;
+pub mod commands;
+pub mod control;
+pub mod distributor;
+pub mod download;
+pub mod health;
+pub mod hooks;
+pub mod logs;
+pub mod process;
+pub mod restart;
+pub mod schedule;
+pub mod shutdown;
+pub mod startup;
+pub mod state;
+pub mod updates;
+
+static EVENTS_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+static SHUTDOWN_CANCELLED_START: &str = "start cancelled: Stainless is shutting down";
+
+/// Settings every server has, whichever backend provides its server application. Backends flatten
+/// them into their own configuration, next to the settings specific to them.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+// Unknown fields are rejected by the configuration the settings are flattened into, which serde
+// cannot do from here; the schema has to be told separately.
+#[schemars(deny_unknown_fields)]
+pub struct ServerSettings {
+    #[serde(skip)]
+    pub server_name: String,
+    /// Arguments passed to the JVM, such as `-Xmx4G`.
+    pub jvm_arguments: Vec<String>,
+    /// Directory holding the server's jar, world and saved state. Relative paths start from the
+    /// Stainless home directory. Defaults to `servers/<name>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<PathBuf>,
+    /// When and how quickly the server is restarted after it exits.
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// How long Stainless waits for the server to stop when Stainless shuts down.
+    #[serde(default)]
+    pub shutdown: ShutdownTimeouts,
+    /// Restarts the server when it stops answering status requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+    /// Shell commands run before and after the server is updated, started and stopped.
+    #[serde(default)]
+    pub hooks: Hooks,
+    /// Checks for newer builds while the server runs, staging them for its next start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updates: Option<BackgroundUpdates>,
+    #[serde(skip)]
+    pub resolved_working_directory: PathBuf,
+}
 
 pub trait Server<S: Server<S, A>, A: ServerApplication<S, A>> {
-    fn server_name(&self) -> &str;
-    fn jvm_arguments(&self) -> &Vec<String>;
-    fn load_saved_server_app(&self) -> crate::Result<A>;
-    fn client_info_file_path(&self) -> String;
+    fn settings(&self) -> &ServerSettings;
     fn default_version_check_client(&self) -> A;
+
+    fn server_name(&self) -> &str {
+        self.settings().server_name.as_str()
+    }
+
+    fn jvm_arguments(&self) -> &Vec<String> {
+        self.settings().jvm_arguments.as_ref()
+    }
+
+    fn working_directory(&self) -> &Path {
+        &self.settings().resolved_working_directory
+    }
+
+    fn stop_command(&self) -> &str {
+        "stop"
+    }
+
+    fn save_command(&self) -> Option<&str> {
+        Some("save-all")
+    }
+
+    fn broadcast_command(&self) -> Option<&str> {
+        Some("say")
+    }
+
+    fn restart_policy(&self) -> &RestartPolicy {
+        &self.settings().restart
+    }
+
+    fn shutdown_timeouts(&self) -> &ShutdownTimeouts {
+        &self.settings().shutdown
+    }
+
+    fn health_check(&self) -> Option<&HealthCheck> {
+        self.settings().health_check.as_ref()
+    }
+
+    fn hooks(&self) -> &Hooks {
+        &self.settings().hooks
+    }
+
+    fn background_updates(&self) -> Option<&BackgroundUpdates> {
+        self.settings().updates.as_ref()
+    }
+
+    fn load_saved_server_app(&self) -> crate::Result<A> {
+        let saved_client: A = read_server_info(&self.client_info_file_path())?;
+
+        println!(
+            "{} Found existing server: {}",
+            CHECK_MARK.glyph,
+            saved_client.application_name()
+        );
+
+        Ok(saved_client)
+    }
+
+    fn load_staged_server_app(&self) -> crate::Result<A> {
+        read_server_info(
+            &staged_server_directory(self.working_directory()).join(SERVER_INFO_FILE_NAME),
+        )
+    }
+
+    fn client_info_file_path(&self) -> PathBuf {
+        self.working_directory().join(SERVER_INFO_FILE_NAME)
+    }
 }
 
 #[async_trait]
-pub trait ServerApplication<C: Server<C, A>, A: ServerApplication<C, A>> {
+pub trait ServerApplication<C: Server<C, A>, A: ServerApplication<C, A>>:
+    Display + Serialize + DeserializeOwned
+{
     fn application_name(&self) -> &str;
+    fn build_name(&self) -> String;
+    /// Whether this is a build the configuration asks for, as far as can be told without checking
+    /// for newer builds.
+    fn matches_config(&self, config: &C) -> bool;
     async fn check_for_updated_server(
         &self,
         config: &C,
         http_client: &Client,
     ) -> crate::Result<Option<A>>;
-    async fn download_server(&self, http_client: &Client) -> crate::Result<()>;
-    fn delete_server(&self) -> crate::Result<()>;
-    fn save_server_info(&self, client_config: &C) -> crate::Result<()>;
-    fn delete_server_info(&self, client_config: &C) -> crate::Result<()>;
+    async fn download_server(
+        &self,
+        config: &C,
+        directory: &Path,
+        http_client: &Client,
+    ) -> crate::Result<()>;
+    fn delete_server(&self, config: &C, replacement: Option<&A>) -> crate::Result<()>;
     async fn start_server(
         &self,
         config: &C,
-        input_receiver: &mut Receiver<u8>,
+        channels: ProcessChannels<'_>,
     ) -> crate::Result<ExitStatus>;
+
+    fn save_server_info(&self, client_config: &C) -> crate::Result<()> {
+        write_server_info(self, &client_config.client_info_file_path())
+    }
+
+    fn stage_server_info(&self, client_config: &C) -> crate::Result<()> {
+        write_server_info(
+            self,
+            &staged_server_directory(client_config.working_directory()).join(SERVER_INFO_FILE_NAME),
+        )
+    }
+
+    #[allow(dead_code)]
+    fn delete_server_info(&self, client_config: &C) -> crate::Result<()> {
+        fs::remove_file(client_config.client_info_file_path())?;
+
+        Ok(())
+    }
 }
 
-pub async fn begin_server_task(server_type: &ServerType, http_client: &Client) {
-    let control_socket_result = create_control_socket().await;
+fn read_server_info<A: DeserializeOwned>(path: &Path) -> crate::Result<A> {
+    let mut saved_client_file = File::open(path)?;
+    let save_config = bincode::config::standard().write_fixed_array_length();
+
+    Ok(bincode::serde::decode_from_std_read(
+        &mut saved_client_file,
+        save_config,
+    )?)
+}
+
+fn write_server_info<A: Serialize + ?Sized>(server_app: &A, path: &Path) -> crate::Result<()> {
+    let mut client_info_file = File::create(path)?;
+    let save_config = bincode::config::standard().write_fixed_array_length();
+    bincode::serde::encode_into_std_write(server_app, &mut client_info_file, save_config)?;
+
+    Ok(())
+}
+
+pub async fn begin_server_task(
+    config_updates: ConfigUpdates,
+    http_client: &Client,
+    events_file: Option<PathBuf>,
+) -> crate::Result<()> {
+    let stop_commands = config_updates
+        .borrow()
+        .servers
+        .iter()
+        .map(|(name, server_type)| (name.clone(), server_type.stop_command().to_string()))
+        .collect();
+    let control_socket_result = create_control_socket(stop_commands).await;
 
     match control_socket_result {
         Ok(mut socket) => {
-            select! {
-                control_thread_result = socket.control_thread => {
+            let stop_intents = socket
+                .server_channels
+                .values()
+                .map(|channels| channels.stop_intent.clone())
+                .collect();
+            let shutdown = listen_for_shutdown_signals(stop_intents);
+            let (state_transitions, _) = broadcast::channel(STATE_TRANSITION_CAPACITY);
+            let events_task = events_file.map(|events_file| {
+                tokio::spawn(write_state_transitions(
+                    events_file,
+                    state_transitions.subscribe(),
+                ))
+            });
+
+            let supervise_result = select! {
+                control_thread_result = &mut socket.control_thread => {
                     match control_thread_result {
                         Ok(thread_run_result) => match thread_run_result {
                             Ok(_) => println!("{} Control thread exited without error", INFORMATION.glyph),
@@ -54,72 +262,334 @@ pub async fn begin_server_task(server_type: &ServerType, http_client: &Client) {
                         },
                         Err(e) => println!("{} Error encountered while spawning control: {}", CROSS_MARK.glyph, e),
                     }
+                    Ok(())
                 }
-                _ = initialize_server_loop(server_type, http_client, &mut socket.control_receiver) => {}
+                supervise_result = supervise_servers(config_updates, http_client, socket.server_channels, state_transitions, shutdown) => supervise_result,
+            };
+
+            // The final transitions are still being written once the servers have stopped.
+            if let Some(events_task) = events_task {
+                let _ = tokio::time::timeout(EVENTS_DRAIN_TIMEOUT, events_task).await;
             }
+
+            supervise_result
         }
-        Err(e) => println!("{} Error making control socket: {}", CROSS_MARK.glyph, e),
+        Err(e) => Err(Error::msg(format!("Error making control socket: {}", e))),
     }
 }
 
-async fn initialize_server_loop(
-    server_type: &ServerType,
+async fn supervise_servers(
+    config_updates: ConfigUpdates,
     http_client: &Client,
-    input_receiver: &mut Receiver<u8>,
-) {
-    println!("{} Entering server loop...", INFORMATION.glyph);
+    mut server_channels: HashMap<String, ServerChannels>,
+    state_transitions: StateTransitionSender,
+    shutdown: ShutdownReceiver,
+) -> crate::Result<()> {
+    let server_names: Vec<String> = config_updates.borrow().servers.keys().cloned().collect();
+    let mut server_tasks = Vec::new();
 
-    loop {
-        println!("{} Starting server...", INFORMATION.glyph);
+    for name in server_names {
+        let channels = match server_channels.remove(&name) {
+            Some(channels) => channels,
+            None => {
+                println!(
+                    "{} No input channel exists for server {}!",
+                    CROSS_MARK.glyph, name
+                );
+                continue;
+            }
+        };
 
-        if let Err(e) = run_configured_server(server_type, http_client, input_receiver).await {
+        let server_task = tokio::spawn(initialize_server_loop(
+            name.clone(),
+            config_updates.clone(),
+            http_client.clone(),
+            channels,
+            state_transitions.clone(),
+            shutdown.clone(),
+        ));
+        server_tasks.push((name, server_task));
+    }
+
+    let mut failures = vec![];
+    for (name, server_task) in server_tasks {
+        match server_task.await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => failures.push(e.to_string()),
+            Err(e) => {
+                println!(
+                    "{} Server task for {} failed unexpectedly: {}",
+                    CROSS_MARK.glyph, name, e
+                );
+                failures.push(format!("server task for {} failed unexpectedly", name));
+            }
+        }
+    }
+
+    println!("{} All servers have stopped!", INFORMATION.glyph);
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::msg(format!(
+            "Not every server stopped cleanly: {}",
+            failures.join("; ")
+        )))
+    }
+}
+
+/// Runs a server and restarts it as its restart policy says until it stops for good. Fails when the
+/// server's last run failed or Stainless gave up restarting it.
+async fn initialize_server_loop(
+    server_name: String,
+    config_updates: ConfigUpdates,
+    http_client: Client,
+    channels: ServerChannels,
+    state_transitions: StateTransitionSender,
+    mut shutdown: ShutdownReceiver,
+) -> crate::Result<()> {
+    let ServerChannels {
+        mut input_receiver,
+        input_sender,
+        stop_intent,
+        log_events,
+        addressed_input,
+    } = channels;
+
+    println!(
+        "{} Entering server loop for {}...",
+        INFORMATION.glyph, server_name
+    );
+
+    let mut previous_server_type: Option<ServerType> = None;
+    let mut restart_tracker = RestartTracker::default();
+    let interactive = control::is_interactive();
+    let lifecycle = ServerLifecycle::new(server_name.clone(), state_transitions);
+    let mut failure = None;
+
+    while !*shutdown.borrow() {
+        let server_type = match config_updates.borrow().servers.get(&server_name) {
+            Some(server_type) => server_type.clone(),
+            None => {
+                println!(
+                    "{} Server {} was removed from the configuration and will not be restarted",
+                    INFORMATION.glyph, server_name
+                );
+                break;
+            }
+        };
+
+        if matches!(&previous_server_type, Some(previous) if *previous != server_type) {
             println!(
-                "{} Server encountered unrecoverable error: {}",
-                CROSS_MARK.glyph, e
+                "{} Applying updated configuration to {}",
+                CHECK_MARK.glyph, server_name
             );
+        }
+        previous_server_type = Some(server_type.clone());
+
+        println!("{} Starting server {}...", INFORMATION.glyph, server_name);
+        stop_intent.take();
+        // Entered before the tasks below subscribe, so that they wait for this run to be ready
+        // rather than reading the end of the previous one.
+        lifecycle.transition(ServerState::CheckingUpdates);
+        // Both tasks belong to this run of the server and are aborted when it ends, however it
+        // ends.
+        let restart_schedule_task = AbortOnDrop(tokio::spawn(run_restart_schedule(
+            server_name.clone(),
+            server_type.clone(),
+            input_sender.clone(),
+            stop_intent.clone(),
+            lifecycle.subscribe(),
+        )));
+        let background_updates_task = AbortOnDrop(tokio::spawn(run_background_updates(
+            server_type.clone(),
+            http_client.clone(),
+            input_sender.clone(),
+            stop_intent.clone(),
+            lifecycle.subscribe(),
+        )));
+
+        let exit_status = match run_configured_server(
+            &server_type,
+            &http_client,
+            ProcessChannels {
+                input_receiver: &mut input_receiver,
+                stop_intent: &stop_intent,
+                lifecycle: &lifecycle,
+                log_events: &log_events,
+                shutdown: &mut shutdown,
+            },
+        )
+        .await
+        {
+            Ok(exit_status) => exit_status,
+            // A server that could not be run is restarted like one that crashed, so that a
+            // download that failed for a moment is retried with the same backoff.
+            Err(e) => {
+                println!(
+                    "{} Server {} could not be run: {}",
+                    CROSS_MARK.glyph, server_name, e
+                );
+                None
+            }
+        };
+        drop(restart_schedule_task);
+        drop(background_updates_task);
+
+        // The server was not started because Stainless is shutting down.
+        if exit_status.is_none() && *shutdown.borrow() {
             break;
         }
 
-        let should_stop_result = control::server_should_stop(input_receiver).await;
+        let outcome = RunOutcome::classify(exit_status, stop_intent.take());
+        failure = match outcome {
+            RunOutcome::FailedShutdown(_) | RunOutcome::Failed => {
+                Some(format!("server {} failed", server_name))
+            }
+            RunOutcome::Shutdown(_) | RunOutcome::Exited => None,
+        };
+        report_run_outcome(&server_name, &outcome);
+        lifecycle.transition(match outcome {
+            RunOutcome::Shutdown(_) | RunOutcome::Exited => ServerState::Stopped,
+            RunOutcome::FailedShutdown(_) | RunOutcome::Failed => ServerState::Crashed,
+        });
+
+        let automatic_restart = matches!(
+            outcome,
+            RunOutcome::Shutdown(
+                StopReason::ScheduledRestart | StopReason::UpdateStaged | StopReason::Unresponsive
+            ) | RunOutcome::FailedShutdown(StopReason::Unresponsive)
+        );
+        let mut decision = match outcome {
+            RunOutcome::Shutdown(StopReason::ScheduledRestart | StopReason::UpdateStaged) => {
+                RestartDecision::Restart(Duration::ZERO)
+            }
+            RunOutcome::FailedShutdown(StopReason::ScheduledRestart | StopReason::UpdateStaged)
+            | RunOutcome::Shutdown(StopReason::Unresponsive)
+            | RunOutcome::FailedShutdown(StopReason::Unresponsive) => {
+                restart_tracker.decide(server_type.restart_policy(), true)
+            }
+            RunOutcome::Shutdown(_) | RunOutcome::FailedShutdown(_) => RestartDecision::Stop,
+            RunOutcome::Exited => restart_tracker.decide(server_type.restart_policy(), false),
+            RunOutcome::Failed => restart_tracker.decide(server_type.restart_policy(), true),
+        };
+
+        if interactive && !automatic_restart && !matches!(decision, RestartDecision::Stop) {
+            let restart_by_default = matches!(decision, RestartDecision::Restart(_));
+            let should_stop_result = control::server_should_stop(
+                &server_name,
+                &mut input_receiver,
+                restart_by_default,
+                addressed_input,
+            )
+            .await;
 
-        match should_stop_result {
-            Ok(should_stop) => {
-                if should_stop {
-                    println!("{} Server stopped!", INFORMATION.glyph);
+            match should_stop_result {
+                Ok(Some(true)) => decision = RestartDecision::Stop,
+                Ok(Some(false)) => decision = RestartDecision::Restart(Duration::ZERO),
+                Ok(None) => {}
+                Err(e) => {
+                    println!(
+                        "{} Stainless encountered error reading input: {}",
+                        CROSS_MARK.glyph, e
+                    );
+                    failure = Some(format!("could not read input for {}: {}", server_name, e));
 
                     break;
-                } else {
-                    println!("{} Restarting...", INFORMATION.glyph)
                 }
             }
-            Err(e) => {
+        }
+
+        match decision {
+            RestartDecision::Restart(backoff) => {
+                if !backoff.is_zero() {
+                    lifecycle.transition(ServerState::BackingOff { delay: backoff });
+                    println!(
+                        "{} Restarting {} in {} second(s)...",
+                        INFORMATION.glyph,
+                        server_name,
+                        backoff.as_secs()
+                    );
+                    select! {
+                        _ = tokio::time::sleep(backoff) => {}
+                        _ = shutdown.changed() => continue,
+                    }
+                }
+
+                println!("{} Restarting {}...", INFORMATION.glyph, server_name)
+            }
+            RestartDecision::Stop => {
+                println!("{} Server {} stopped!", INFORMATION.glyph, server_name);
+
+                break;
+            }
+            RestartDecision::GiveUp => {
                 println!(
-                    "{} Stainless encountered error reading input: {}",
-                    CROSS_MARK.glyph, e
+                    "{} Giving up on {}: it restarted {} time(s) within {} seconds. Fix the server and restart Stainless to run it again",
+                    CROSS_MARK.glyph,
+                    server_name,
+                    restart_tracker.recent_restarts(),
+                    server_type.restart_policy().restart_window_seconds
                 );
+                failure = Some(format!("gave up on server {}", server_name));
 
                 break;
             }
         }
     }
+
+    lifecycle.transition(ServerState::Stopped);
+
+    match failure {
+        Some(failure) => Err(Error::msg(failure)),
+        None => Ok(()),
+    }
 }
 
+/// Installs or updates a server and runs it until it exits. Returns its exit status, or `None` when
+/// it could not be started or failed while running. Fails when no server application could be
+/// installed to run.
 pub async fn run_configured_server(
     server_type: &ServerType,
     http_client: &Client,
-    input_receiver: &mut Receiver<u8>,
-) -> crate::Result<()> {
-    let ServerType::PaperMC(server) = server_type;
+    channels: ProcessChannels<'_>,
+) -> crate::Result<Option<ExitStatus>> {
+    match server_type {
+        ServerType::PaperMC(server) => run_server(server, http_client, channels).await,
+        ServerType::Vanilla(server) => run_server(server, http_client, channels).await,
+        ServerType::Fabric(server) => run_server(server, http_client, channels).await,
+        ServerType::Forge(server) => run_server(server, http_client, channels).await,
+        ServerType::Fork(server) => run_server(server, http_client, channels).await,
+        ServerType::Custom(server) => run_server(server, http_client, channels).await,
+    }
+}
+
+/// Aborts a task when dropped.
+struct AbortOnDrop<T>(JoinHandle<T>);
 
-    run_server(server, http_client, input_receiver).await
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
 }
 
 async fn run_server<S: Server<S, A>, A: ServerApplication<S, A>>(
     server: &S,
     http_client: &Client,
-    input_receiver: &mut Receiver<u8>,
-) -> crate::Result<()> {
-    let server_app = acquire_server_app(server, http_client).await;
+    channels: ProcessChannels<'_>,
+) -> crate::Result<Option<ExitStatus>> {
+    channels.lifecycle.transition(ServerState::CheckingUpdates);
+    let server_app = match server.background_updates() {
+        // Newer builds are staged while the server runs, so starting it never waits on a download.
+        Some(_) => match load_installed_server_app(server).await {
+            Some(server_app) if server_app.matches_config(server) => Some(server_app),
+            // The configuration changed since this build was installed, so it is checked now.
+            server_app => {
+                update_server_app(server_app, server, http_client, Some(channels.lifecycle)).await
+            }
+        },
+        None => acquire_server_app(server, http_client, Some(channels.lifecycle)).await,
+    };
 
     let run_result = match &server_app {
         Some(server_app) => {
@@ -128,7 +598,26 @@ async fn run_server<S: Server<S, A>, A: ServerApplication<S, A>>(
                 CHECK_MARK.glyph,
                 server_app.application_name()
             );
-            server_app.start_server(server, input_receiver).await
+
+            let hook_context =
+                HookContext::new(server).with("STAINLESS_BUILD", server_app.build_name());
+            // Updating and the pre-start hook can take a while, and a shutdown requested meanwhile
+            // must not start the server.
+            if *channels.shutdown.borrow() {
+                Err(Error::msg(SHUTDOWN_CANCELLED_START))
+            } else {
+                match run_hook(server.hooks(), Hook::PreStart, &hook_context).await {
+                    Ok(_) if *channels.shutdown.borrow() => {
+                        Err(Error::msg(SHUTDOWN_CANCELLED_START))
+                    }
+                    Ok(_) => {
+                        let run_result = server_app.start_server(server, channels).await;
+                        run_exit_hooks(server, hook_context, &run_result).await;
+                        run_result
+                    }
+                    Err(e) => Err(Error::msg(format!("start cancelled: {}", e))),
+                }
+            }
         }
         None => {
             println!(
@@ -142,14 +631,28 @@ async fn run_server<S: Server<S, A>, A: ServerApplication<S, A>>(
     display_server_result(&run_result);
     save_server_info_if_exists(server, &server_app);
 
-    Ok(())
+    Ok(run_result.ok())
 }
 
 async fn acquire_server_app<S: Server<S, A>, A: ServerApplication<S, A>>(
     server: &S,
     http_client: &Client,
+    lifecycle: Option<&ServerLifecycle>,
+) -> Option<A> {
+    let existing_server_app = load_installed_server_app(server).await;
+
+    update_server_app(existing_server_app, server, http_client, lifecycle).await
+}
+
+/// Loads the installed server application, first swapping in a build staged in the background.
+async fn load_installed_server_app<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
 ) -> Option<A> {
-    let existing_server_app = match server.load_saved_server_app() {
+    if let Some(server_app) = promote_staged_server_app(server).await {
+        return Some(server_app);
+    }
+
+    match server.load_saved_server_app() {
         Ok(client_found) => Some(client_found),
         Err(e) => {
             println!(
@@ -163,15 +666,14 @@ async fn acquire_server_app<S: Server<S, A>, A: ServerApplication<S, A>>(
             );
             None
         }
-    };
-
-    update_server_app(existing_server_app, server, http_client).await
+    }
 }
 
 async fn update_server_app<S: Server<S, A>, A: ServerApplication<S, A>>(
     existing_server_app: Option<A>,
     server: &S,
     http_client: &Client,
+    lifecycle: Option<&ServerLifecycle>,
 ) -> Option<A> {
     let default_server_app = server.default_version_check_client();
     let checking_server_app = match &existing_server_app {
@@ -184,8 +686,14 @@ async fn update_server_app<S: Server<S, A>, A: ServerApplication<S, A>>(
         .await
     {
         Ok(update_result) => {
-            replace_server_app_if_new_one_exists(update_result, existing_server_app, http_client)
-                .await
+            replace_server_app_if_new_one_exists(
+                server,
+                update_result,
+                existing_server_app,
+                http_client,
+                lifecycle,
+            )
+            .await
         }
         Err(e) => {
             println!(
@@ -202,37 +710,94 @@ async fn update_server_app<S: Server<S, A>, A: ServerApplication<S, A>>(
 }
 
 async fn replace_server_app_if_new_one_exists<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
     update_result: Option<A>,
     existing_server_app: Option<A>,
     http_client: &Client,
+    lifecycle: Option<&ServerLifecycle>,
 ) -> Option<A> {
-    match update_result {
-        Some(updated_server_app) => match updated_server_app.download_server(http_client).await {
-            Ok(_) => {
-                if let Some(app) = existing_server_app {
-                    match app.delete_server() {
-                        Ok(_) => println!(
-                            "{} Successfully deleted deprecated server app!",
-                            CHECK_MARK.glyph
-                        ),
-                        Err(e) => println!(
-                            "{} Failed to delete old server app: {}",
-                            CROSS_MARK.glyph, e
-                        ),
-                    }
+    let updated_server_app = match update_result {
+        Some(updated_server_app) => updated_server_app,
+        None => return existing_server_app,
+    };
+
+    if let Some(lifecycle) = lifecycle {
+        lifecycle.transition(ServerState::Downloading);
+    }
+
+    let mut hook_context =
+        HookContext::new(server).with("STAINLESS_NEW_BUILD", updated_server_app.build_name());
+    if let Some(app) = &existing_server_app {
+        hook_context = hook_context.with("STAINLESS_OLD_BUILD", app.build_name());
+    }
+    run_hook_reporting_failure(server.hooks(), Hook::PreUpdate, &hook_context).await;
+
+    match updated_server_app
+        .download_server(server, server.working_directory(), http_client)
+        .await
+    {
+        Ok(_) => {
+            // A server app replaced under the same name was already overwritten by the download.
+            if let Some(app) = existing_server_app
+                .filter(|app| app.application_name() != updated_server_app.application_name())
+            {
+                match app.delete_server(server, Some(&updated_server_app)) {
+                    Ok(_) => println!(
+                        "{} Successfully deleted deprecated server app!",
+                        CHECK_MARK.glyph
+                    ),
+                    Err(e) => println!(
+                        "{} Failed to delete old server app: {}",
+                        CROSS_MARK.glyph, e
+                    ),
                 }
-                Some(updated_server_app)
             }
-            Err(e) => {
-                println!(
-                    "{} Failed to download updated server: {}",
-                    emoji::symbols::warning::WARNING.glyph,
-                    e
-                );
-                existing_server_app
-            }
-        },
-        None => existing_server_app,
+            run_hook_reporting_failure(server.hooks(), Hook::PostUpdate, &hook_context).await;
+            Some(updated_server_app)
+        }
+        Err(e) => {
+            println!(
+                "{} Failed to download updated server: {}",
+                emoji::symbols::warning::WARNING.glyph,
+                e
+            );
+            existing_server_app
+        }
+    }
+}
+
+/// Runs the hooks for a server that has exited, along with its crash hooks when it failed.
+async fn run_exit_hooks<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
+    mut hook_context: HookContext,
+    run_result: &crate::Result<ExitStatus>,
+) {
+    if let Ok(Some(exit_code)) = run_result.as_ref().map(|exit_status| exit_status.code()) {
+        hook_context = hook_context.with("STAINLESS_EXIT_CODE", exit_code);
+    }
+
+    run_hook_reporting_failure(server.hooks(), Hook::PostStop, &hook_context).await;
+
+    if !matches!(run_result, Ok(exit_status) if exit_status.success()) {
+        run_hook_reporting_failure(server.hooks(), Hook::OnCrash, &hook_context).await;
+    }
+}
+
+fn report_run_outcome(server_name: &str, outcome: &RunOutcome) {
+    match outcome {
+        RunOutcome::Shutdown(reason) => println!(
+            "{} Server {} shut down cleanly after {}",
+            CHECK_MARK.glyph, server_name, reason
+        ),
+        RunOutcome::FailedShutdown(reason) => println!(
+            "{} Server {} failed while shutting down after {}",
+            CROSS_MARK.glyph, server_name, reason
+        ),
+        RunOutcome::Exited => println!(
+            "{} Server {} exited on its own without a stop request",
+            INFORMATION.glyph, server_name
+        ),
+        RunOutcome::Failed => println!("{} Server {} failed", CROSS_MARK.glyph, server_name),
     }
 }
 
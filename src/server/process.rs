@@ -0,0 +1,320 @@
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+
+use anyhow::Error;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
+use emoji::symbols::warning::WARNING;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::Receiver;
+use tokio::{pin, select};
+
+use crate::server::control::stop::{StopIntent, StopReason};
+use crate::server::health::{request_thread_dump, wait_until_unhealthy};
+use crate::server::hooks::{run_hook_reporting_failure, Hook, HookContext};
+use crate::server::logs::{LogEvent, LogEventReceiver, LogEventSender, LogParser};
+use crate::server::shutdown::ShutdownReceiver;
+use crate::server::startup::record_startup_time;
+use crate::server::state::{ServerLifecycle, ServerState};
+use crate::server::{Server, ServerApplication};
+
+static OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Channels a server process is driven through and observed on while it runs.
+pub struct ProcessChannels<'a> {
+    pub input_receiver: &'a mut Receiver<String>,
+    pub stop_intent: &'a StopIntent,
+    pub lifecycle: &'a ServerLifecycle,
+    pub log_events: &'a LogEventSender,
+    pub shutdown: &'a mut ShutdownReceiver,
+}
+
+/// Runs a server process until it exits, forwarding console input to it and passing its output
+/// through to the console. The output is parsed into log events, and the server is running once
+/// it logs that it has started. When Stainless shuts down or the server stops answering health
+/// checks, the server is saved and stopped through its console, then terminated and finally
+/// killed if it does not exit in time.
+pub async fn run_server_process<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server_app: &A,
+    server: &S,
+    mut command: Command,
+    channels: ProcessChannels<'_>,
+) -> crate::Result<ExitStatus> {
+    command.stdin(Stdio::piped()).stdout(Stdio::piped());
+    detach_from_terminal_signals(&mut command);
+
+    channels.lifecycle.transition(ServerState::Starting);
+    let mut server_process = command.spawn()?;
+    let mut child_in = server_process.stdin.take().unwrap();
+
+    let log_receiver = channels.log_events.subscribe();
+    let output_task = tokio::spawn(pass_through_output(
+        server_process.stdout.take().unwrap(),
+        channels.log_events.clone(),
+    ));
+
+    let run_result = supervise_server_process(
+        server_app,
+        server,
+        &mut server_process,
+        &mut child_in,
+        log_receiver,
+        channels,
+    )
+    .await;
+
+    // Let the last of the server's output reach the console before reporting how it exited.
+    let _ = tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, output_task).await;
+
+    run_result
+}
+
+async fn supervise_server_process<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server_app: &A,
+    server: &S,
+    server_process: &mut Child,
+    child_in: &mut ChildStdin,
+    mut log_receiver: LogEventReceiver,
+    channels: ProcessChannels<'_>,
+) -> crate::Result<ExitStatus> {
+    let ProcessChannels {
+        input_receiver,
+        stop_intent,
+        lifecycle,
+        shutdown,
+        ..
+    } = channels;
+
+    let unhealthy = wait_until_unhealthy(
+        server.server_name(),
+        server.working_directory(),
+        server.health_check(),
+        lifecycle.subscribe(),
+    );
+    pin!(unhealthy);
+    let mut awaiting_ready = true;
+
+    while !*shutdown.borrow() {
+        select! {
+            log_event = log_receiver.recv(), if awaiting_ready => {
+                match log_event {
+                    Ok(LogEvent::Ready { startup }) => {
+                        awaiting_ready = false;
+                        announce_ready(server_app, server, lifecycle, startup);
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => awaiting_ready = false,
+                }
+            }
+            _ = &mut unhealthy => {
+                stop_intent.record(StopReason::Unresponsive);
+                if matches!(server.health_check(), Some(health_check) if health_check.thread_dump) {
+                    request_thread_dump(server.server_name(), server_process).await;
+                }
+                break;
+            }
+            receive_result = input_receiver.recv() => {
+                match receive_result {
+                    Some(line) => {
+                        write_line(child_in, &line).await?;
+                        // Stop requests are recorded before their stop command is sent.
+                        if stop_intent.is_recorded() {
+                            lifecycle.transition(ServerState::Stopping);
+                        }
+                    }
+                    None => return Err(Error::msg("Input channel broke."))
+                }
+            }
+            shutdown_result = shutdown.changed() => {
+                if shutdown_result.is_err() {
+                    return Err(Error::msg("Shutdown channel broke."));
+                }
+            }
+            server_result = server_process.wait() => {
+                match server_result {
+                    Ok(output) => {
+                        return Ok(output)
+                    },
+                    Err(e) => {
+                        println!("{} Error occurred running server: {}", CROSS_MARK.glyph, e);
+                        return Err(Error::from(e))
+                    }
+                }
+            }
+        }
+    }
+
+    lifecycle.transition(ServerState::Stopping);
+    shut_down_server_process(server, server_process, child_in).await
+}
+
+/// Prints each line the server writes and publishes the events parsed from it.
+async fn pass_through_output(output: ChildStdout, log_events: LogEventSender) {
+    let mut output = BufReader::new(output);
+    let mut log_parser = LogParser::default();
+    let mut line = vec![];
+
+    loop {
+        line.clear();
+        match output.read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches(&['\r', '\n'][..]);
+        println!("{}", line);
+
+        // Nobody may be listening, in which case the events are simply dropped.
+        for log_event in log_parser.parse_line(line) {
+            let _ = log_events.send(log_event);
+        }
+    }
+
+    if let Some(log_event) = log_parser.finish() {
+        let _ = log_events.send(log_event);
+    }
+}
+
+fn announce_ready<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server_app: &A,
+    server: &S,
+    lifecycle: &ServerLifecycle,
+    startup: Duration,
+) {
+    println!(
+        "{} Server {} is ready after {:.2}s",
+        CHECK_MARK.glyph,
+        server.server_name(),
+        startup.as_secs_f64()
+    );
+
+    match record_startup_time(
+        server.working_directory(),
+        &server_app.build_name(),
+        startup,
+    ) {
+        Ok(startup_times) if startup_times.starts > 1 => println!(
+            "{} {} starts in {:.2}s on average over {} starts",
+            INFORMATION.glyph,
+            server_app.build_name(),
+            startup_times.average_seconds,
+            startup_times.starts
+        ),
+        Ok(_) => {}
+        Err(e) => println!(
+            "{} Unable to record the startup time of {}: {}",
+            WARNING.glyph,
+            server.server_name(),
+            e
+        ),
+    }
+
+    lifecycle.transition(ServerState::Running { startup });
+
+    let hooks = server.hooks().clone();
+    let hook_context = HookContext::new(server)
+        .with("STAINLESS_BUILD", server_app.build_name())
+        .with("STAINLESS_STARTUP_SECONDS", startup.as_secs_f64());
+    // The server keeps running while the hook does, so the hook must not hold up its console.
+    tokio::spawn(async move {
+        run_hook_reporting_failure(&hooks, Hook::PostStart, &hook_context).await;
+    });
+}
+
+async fn shut_down_server_process<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
+    server_process: &mut Child,
+    child_in: &mut ChildStdin,
+) -> crate::Result<ExitStatus> {
+    let timeouts = server.shutdown_timeouts();
+
+    println!(
+        "{} Saving and stopping server {}...",
+        INFORMATION.glyph,
+        server.server_name()
+    );
+
+    let mut console_commands: Vec<&str> = server.save_command().into_iter().collect();
+    console_commands.push(server.stop_command());
+    for console_command in console_commands {
+        if let Err(e) = write_line(child_in, console_command).await {
+            println!(
+                "{} Unable to send {} to server {}: {}",
+                WARNING.glyph,
+                console_command,
+                server.server_name(),
+                e
+            );
+        }
+    }
+
+    let stop_timeout = Duration::from_secs(timeouts.stop_timeout_seconds);
+    if let Ok(exit_status) = tokio::time::timeout(stop_timeout, server_process.wait()).await {
+        return Ok(exit_status?);
+    }
+
+    println!(
+        "{} Server {} did not stop within {} seconds; terminating it...",
+        WARNING.glyph,
+        server.server_name(),
+        timeouts.stop_timeout_seconds
+    );
+    terminate(server_process);
+
+    let terminate_timeout = Duration::from_secs(timeouts.terminate_timeout_seconds);
+    if let Ok(exit_status) = tokio::time::timeout(terminate_timeout, server_process.wait()).await {
+        return Ok(exit_status?);
+    }
+
+    println!(
+        "{} Server {} did not exit within {} seconds of being terminated; killing it...",
+        WARNING.glyph,
+        server.server_name(),
+        timeouts.terminate_timeout_seconds
+    );
+    server_process.kill().await?;
+
+    Ok(server_process.wait().await?)
+}
+
+async fn write_line(child_in: &mut ChildStdin, line: &str) -> crate::Result<()> {
+    child_in.write_all(line.as_bytes()).await?;
+    child_in.write_u8(b'\n').await?;
+    child_in.flush().await?;
+
+    Ok(())
+}
+
+/// Moves the server into its own process group so that a Ctrl-C in the terminal reaches only
+/// Stainless, which then stops the server itself.
+#[cfg(unix)]
+fn detach_from_terminal_signals(command: &mut Command) {
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setpgid(0, 0) == 0 {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            }
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn detach_from_terminal_signals(_command: &mut Command) {}
+
+#[cfg(unix)]
+fn terminate(server_process: &mut Child) {
+    if let Some(process_id) = server_process.id() {
+        unsafe {
+            libc::kill(process_id as libc::pid_t, libc::SIGTERM);
+        }
+    }
+}
+
+/// Only Unix can ask a process to terminate; elsewhere the server is killed after the timeout.
+#[cfg(not(unix))]
+fn terminate(_server_process: &mut Child) {}
@@ -0,0 +1,306 @@
+use std::collections::VecDeque;
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::server::control::stop::StopReason;
+use crate::server::schedule::RestartSchedule;
+
+/// How Stainless restarts a server after it exits.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RestartPolicy {
+    /// When to restart the server: `always`, `on-failure` or `never`.
+    pub policy: RestartCondition,
+    /// Delay before restarting after a failure. The delay doubles with every consecutive failure.
+    pub initial_backoff_seconds: u64,
+    /// Longest delay between restarts after consecutive failures.
+    pub max_backoff_seconds: u64,
+    /// Most restarts allowed within `restart_window_seconds`. Stainless gives up on the server
+    /// once it would restart more often than this.
+    pub max_restarts: u32,
+    /// Length of the window, in seconds, that `max_restarts` applies to.
+    pub restart_window_seconds: u64,
+    /// Restarts the server on a schedule, warning players beforehand. Scheduled restarts do not
+    /// count towards `max_restarts`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<RestartSchedule>,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            policy: RestartCondition::Always,
+            initial_backoff_seconds: 5,
+            max_backoff_seconds: 300,
+            max_restarts: 5,
+            restart_window_seconds: 600,
+            schedule: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartCondition {
+    /// Restart the server whenever it exits.
+    Always,
+    /// Restart the server only when it exits with a failure.
+    OnFailure,
+    /// Never restart the server.
+    Never,
+}
+
+/// How a run of a server ended, judged by its exit status and whether it was asked to stop.
+pub enum RunOutcome {
+    Shutdown(StopReason),
+    FailedShutdown(StopReason),
+    Exited,
+    Failed,
+}
+
+impl RunOutcome {
+    pub fn classify(
+        exit_status: Option<ExitStatus>,
+        stop_reason: Option<StopReason>,
+    ) -> RunOutcome {
+        let succeeded = matches!(exit_status, Some(status) if status.success());
+
+        match (stop_reason, succeeded) {
+            (Some(reason), true) => RunOutcome::Shutdown(reason),
+            (Some(reason), false) => RunOutcome::FailedShutdown(reason),
+            (None, true) => RunOutcome::Exited,
+            (None, false) => RunOutcome::Failed,
+        }
+    }
+}
+
+pub enum RestartDecision {
+    Restart(Duration),
+    Stop,
+    GiveUp,
+}
+
+/// Restarts of one server within the current restart window, and how many runs in a row failed.
+#[derive(Default)]
+pub struct RestartTracker {
+    restarts: VecDeque<Instant>,
+    consecutive_failures: u32,
+}
+
+impl RestartTracker {
+    pub fn decide(&mut self, policy: &RestartPolicy, failed: bool) -> RestartDecision {
+        if failed {
+            self.consecutive_failures += 1;
+        } else {
+            self.consecutive_failures = 0;
+        }
+
+        let should_restart = match policy.policy {
+            RestartCondition::Always => true,
+            RestartCondition::OnFailure => failed,
+            RestartCondition::Never => false,
+        };
+        if !should_restart {
+            return RestartDecision::Stop;
+        }
+
+        let now = Instant::now();
+        let window = Duration::from_secs(policy.restart_window_seconds);
+        while matches!(self.restarts.front(), Some(restart) if now.duration_since(*restart) > window)
+        {
+            self.restarts.pop_front();
+        }
+
+        if self.restarts.len() >= policy.max_restarts as usize {
+            return RestartDecision::GiveUp;
+        }
+        self.restarts.push_back(now);
+
+        RestartDecision::Restart(self.backoff(policy))
+    }
+
+    pub fn recent_restarts(&self) -> usize {
+        self.restarts.len()
+    }
+
+    fn backoff(&self, policy: &RestartPolicy) -> Duration {
+        if self.consecutive_failures == 0 {
+            return Duration::ZERO;
+        }
+
+        let doublings = (self.consecutive_failures - 1).min(32);
+        let backoff_seconds = policy
+            .initial_backoff_seconds
+            .saturating_mul(1 << doublings)
+            .min(policy.max_backoff_seconds);
+
+        Duration::from_secs(backoff_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn restart_delay(decision: RestartDecision) -> Option<Duration> {
+        match decision {
+            RestartDecision::Restart(delay) => Some(delay),
+            RestartDecision::Stop | RestartDecision::GiveUp => None,
+        }
+    }
+
+    fn policy() -> RestartPolicy {
+        RestartPolicy {
+            initial_backoff_seconds: 5,
+            max_backoff_seconds: 30,
+            max_restarts: 100,
+            ..RestartPolicy::default()
+        }
+    }
+
+    #[test]
+    fn doubles_backoff_for_consecutive_failures_up_to_the_maximum() {
+        let policy = policy();
+        let mut tracker = RestartTracker::default();
+        let delays: Vec<u64> = (0..6)
+            .map(|_| {
+                restart_delay(tracker.decide(&policy, true))
+                    .unwrap()
+                    .as_secs()
+            })
+            .collect();
+
+        assert_eq!(delays, vec![5, 10, 20, 30, 30, 30]);
+    }
+
+    #[test]
+    fn resets_backoff_after_a_clean_exit() {
+        let policy = policy();
+        let mut tracker = RestartTracker::default();
+        tracker.decide(&policy, true);
+        tracker.decide(&policy, true);
+
+        assert_eq!(
+            restart_delay(tracker.decide(&policy, false)),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            restart_delay(tracker.decide(&policy, true)),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn does_not_overflow_after_many_failures() {
+        let policy = RestartPolicy {
+            max_backoff_seconds: u64::MAX,
+            ..policy()
+        };
+        let mut tracker = RestartTracker::default();
+        let mut delay = None;
+        for _ in 0..40 {
+            delay = restart_delay(tracker.decide(&policy, true));
+        }
+
+        assert_eq!(delay, Some(Duration::from_secs(5 << 32)));
+    }
+
+    #[test]
+    fn gives_up_after_too_many_restarts_in_the_window() {
+        let policy = RestartPolicy {
+            max_restarts: 2,
+            ..policy()
+        };
+        let mut tracker = RestartTracker::default();
+
+        assert!(restart_delay(tracker.decide(&policy, true)).is_some());
+        assert!(restart_delay(tracker.decide(&policy, true)).is_some());
+        assert!(matches!(
+            tracker.decide(&policy, true),
+            RestartDecision::GiveUp
+        ));
+        assert_eq!(tracker.recent_restarts(), 2);
+    }
+
+    #[test]
+    fn follows_the_restart_condition() {
+        let on_failure = RestartPolicy {
+            policy: RestartCondition::OnFailure,
+            ..policy()
+        };
+        let never = RestartPolicy {
+            policy: RestartCondition::Never,
+            ..policy()
+        };
+        let mut tracker = RestartTracker::default();
+
+        assert!(matches!(
+            tracker.decide(&on_failure, false),
+            RestartDecision::Stop
+        ));
+        assert!(restart_delay(tracker.decide(&on_failure, true)).is_some());
+        assert!(matches!(
+            tracker.decide(&never, true),
+            RestartDecision::Stop
+        ));
+    }
+
+    #[cfg(unix)]
+    fn exit_status(wait_status: i32) -> Option<ExitStatus> {
+        use std::os::unix::process::ExitStatusExt;
+
+        Some(ExitStatus::from_raw(wait_status))
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn classifies_a_clean_exit_after_a_stop_request_as_a_shutdown() {
+        assert!(matches!(
+            RunOutcome::classify(exit_status(0), Some(StopReason::ConsoleCommand)),
+            RunOutcome::Shutdown(StopReason::ConsoleCommand)
+        ));
+        assert!(matches!(
+            RunOutcome::classify(exit_status(0), None),
+            RunOutcome::Exited
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn classifies_a_non_zero_exit_as_a_failure() {
+        // Exit code 1, as encoded in a wait status.
+        assert!(matches!(
+            RunOutcome::classify(exit_status(1 << 8), None),
+            RunOutcome::Failed
+        ));
+        assert!(matches!(
+            RunOutcome::classify(exit_status(1 << 8), Some(StopReason::Signal)),
+            RunOutcome::FailedShutdown(StopReason::Signal)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn classifies_an_exit_by_signal_as_a_failure() {
+        // Killed by SIGKILL, as encoded in a wait status.
+        assert!(matches!(
+            RunOutcome::classify(exit_status(9), None),
+            RunOutcome::Failed
+        ));
+        assert!(matches!(
+            RunOutcome::classify(exit_status(9), Some(StopReason::Unresponsive)),
+            RunOutcome::FailedShutdown(StopReason::Unresponsive)
+        ));
+    }
+
+    #[test]
+    fn classifies_a_run_without_an_exit_status_as_a_failure() {
+        assert!(matches!(
+            RunOutcome::classify(None, None),
+            RunOutcome::Failed
+        ));
+    }
+}
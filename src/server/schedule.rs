@@ -0,0 +1,352 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Error;
+use chrono::{DateTime, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::warning::WARNING;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::Sender;
+
+use crate::config::ServerType;
+use crate::server::control::stop::{StopIntent, StopReason};
+use crate::server::state::{wait_until_ready, ServerStateReceiver};
+
+/// When to restart a server regardless of its state, such as a daily restart at night.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RestartSchedule {
+    /// Cron expression of when to restart: `minute hour day-of-month month day-of-week`, such as
+    /// `0 4 * * *` for 04:00 every day. A leading seconds field may be added.
+    pub cron: String,
+    /// Time zone the cron expression is read in, such as `Europe/Berlin`. Defaults to the time
+    /// zone of the machine running Stainless.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    /// How long before each restart players are warned, such as `10m` or `10s`.
+    #[serde(default = "default_warnings")]
+    pub warnings: Vec<String>,
+}
+
+pub fn default_warnings() -> Vec<String> {
+    ["10m", "5m", "1m", "10s"]
+        .iter()
+        .map(|warning| warning.to_string())
+        .collect()
+}
+
+/// A restart schedule with its expression, time zone and warnings parsed.
+pub struct ScheduledRestarts {
+    schedule: Schedule,
+    time_zone: Option<Tz>,
+    warnings: Vec<Duration>,
+}
+
+impl ScheduledRestarts {
+    pub fn parse(restart_schedule: &RestartSchedule) -> crate::Result<ScheduledRestarts> {
+        Ok(ScheduledRestarts {
+            schedule: parse_cron(&restart_schedule.cron)?,
+            time_zone: match &restart_schedule.time_zone {
+                Some(time_zone) => Some(parse_time_zone(time_zone)?),
+                None => None,
+            },
+            warnings: parse_warnings(&restart_schedule.warnings)?,
+        })
+    }
+
+    fn next_restart(&self) -> Option<DateTime<Utc>> {
+        match self.time_zone {
+            Some(time_zone) => next_in_time_zone(&self.schedule, time_zone),
+            None => next_in_time_zone(&self.schedule, Local),
+        }
+    }
+
+    fn display_time(&self, time: &DateTime<Utc>) -> String {
+        match self.time_zone {
+            Some(time_zone) => time.with_timezone(&time_zone).to_rfc2822(),
+            None => time.with_timezone(&Local).to_rfc2822(),
+        }
+    }
+}
+
+pub fn parse_cron(cron: &str) -> crate::Result<Schedule> {
+    // The cron crate expects a leading seconds field, which crontab expressions do not have.
+    let expression = if cron.split_whitespace().count() == 5 {
+        format!("0 {}", cron)
+    } else {
+        cron.to_string()
+    };
+
+    Schedule::from_str(&expression)
+        .map_err(|e| Error::msg(format!("invalid cron expression \"{}\": {}", cron, e)))
+}
+
+pub fn parse_time_zone(time_zone: &str) -> crate::Result<Tz> {
+    Tz::from_str(time_zone).map_err(|_| {
+        Error::msg(format!(
+            "unknown time zone \"{}\"; expected a name such as Europe/Berlin or UTC",
+            time_zone
+        ))
+    })
+}
+
+/// Parses warning times such as `10m`, returning them longest first.
+pub fn parse_warnings(warnings: &[String]) -> crate::Result<Vec<Duration>> {
+    let mut parsed_warnings = warnings
+        .iter()
+        .map(|warning| parse_duration(warning))
+        .collect::<crate::Result<Vec<_>>>()?;
+    parsed_warnings.sort_unstable_by(|a, b| b.cmp(a));
+    parsed_warnings.dedup();
+
+    Ok(parsed_warnings)
+}
+
+fn parse_duration(duration: &str) -> crate::Result<Duration> {
+    let invalid_duration = || {
+        Error::msg(format!(
+            "invalid warning time \"{}\"; expected a number followed by s, m or h, such as 10m",
+            duration
+        ))
+    };
+
+    let unit_seconds = match duration.chars().last() {
+        Some('s') => 1,
+        Some('m') => 60,
+        Some('h') => 60 * 60,
+        _ => return Err(invalid_duration()),
+    };
+    let amount: u64 = duration[..duration.len() - 1]
+        .parse()
+        .map_err(|_| invalid_duration())?;
+
+    amount
+        .checked_mul(unit_seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid_duration)
+}
+
+fn next_in_time_zone<Z: TimeZone>(schedule: &Schedule, time_zone: Z) -> Option<DateTime<Utc>> {
+    schedule
+        .upcoming(time_zone)
+        .next()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn describe_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (amount, unit) = match (seconds % 60, seconds % (60 * 60)) {
+        (0, 0) if seconds > 0 => (seconds / (60 * 60), "hour"),
+        (0, _) if seconds > 0 => (seconds / 60, "minute"),
+        _ => (seconds, "second"),
+    };
+
+    if amount == 1 {
+        format!("{} {}", amount, unit)
+    } else {
+        format!("{} {}s", amount, unit)
+    }
+}
+
+/// Waits for the next scheduled restart of a server, warning players beforehand, and then stops
+/// the server through its console so that its loop starts it again. Nothing is scheduled until the
+/// server is running, as its console would otherwise feed the next process.
+pub async fn run_restart_schedule(
+    server_name: String,
+    server_type: ServerType,
+    input_sender: Sender<String>,
+    stop_intent: Arc<StopIntent>,
+    mut state: ServerStateReceiver,
+) {
+    let restart_schedule = match &server_type.restart_policy().schedule {
+        Some(restart_schedule) => restart_schedule,
+        None => return,
+    };
+    let scheduled_restarts = match ScheduledRestarts::parse(restart_schedule) {
+        Ok(scheduled_restarts) => scheduled_restarts,
+        Err(e) => {
+            println!(
+                "{} Restart schedule of {} is not used: {}",
+                WARNING.glyph, server_name, e
+            );
+            return;
+        }
+    };
+    if wait_until_ready(&mut state).await.is_none() {
+        return;
+    }
+    let restart_time = match scheduled_restarts.next_restart() {
+        Some(restart_time) => restart_time,
+        None => return,
+    };
+
+    println!(
+        "{} Server {} will restart as scheduled at {}",
+        INFORMATION.glyph,
+        server_name,
+        scheduled_restarts.display_time(&restart_time)
+    );
+
+    restart_with_warnings(
+        &server_name,
+        &server_type,
+        &input_sender,
+        &stop_intent,
+        restart_time,
+        &scheduled_restarts.warnings,
+        StopReason::ScheduledRestart,
+    )
+    .await;
+}
+
+/// Warns players ahead of `restart_time` and then stops the server through its console, recording
+/// `reason` so that its loop starts it again.
+pub async fn restart_with_warnings(
+    server_name: &str,
+    server_type: &ServerType,
+    input_sender: &Sender<String>,
+    stop_intent: &StopIntent,
+    restart_time: DateTime<Utc>,
+    warnings: &[Duration],
+    reason: StopReason,
+) {
+    for warning in warnings {
+        let warning_time = restart_time
+            - chrono::Duration::from_std(*warning).unwrap_or_else(|_| chrono::Duration::zero());
+        if warning_time < Utc::now() {
+            continue;
+        }
+
+        sleep_until(warning_time).await;
+
+        if let Some(broadcast_command) = server_type.broadcast_command() {
+            let warning_message = format!(
+                "{} Server restarts in {}",
+                broadcast_command,
+                describe_duration(*warning)
+            );
+            if input_sender.send(warning_message).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    sleep_until(restart_time).await;
+    // The server is already being stopped for another reason.
+    if stop_intent.is_recorded() {
+        return;
+    }
+
+    println!(
+        "{} Restarting {} for {}...",
+        INFORMATION.glyph, server_name, reason
+    );
+    stop_intent.record(reason);
+
+    let mut console_commands: Vec<&str> = server_type.save_command().into_iter().collect();
+    console_commands.push(server_type.stop_command());
+    for console_command in console_commands {
+        if input_sender
+            .send(console_command.to_string())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+async fn sleep_until(time: DateTime<Utc>) {
+    let remaining = (time - Utc::now()).to_std().unwrap_or_default();
+
+    tokio::time::sleep(remaining).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warnings(warnings: &[&str]) -> Vec<String> {
+        warnings.iter().map(|warning| warning.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("10s").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(5 * 60));
+        assert_eq!(
+            parse_duration("2h").unwrap(),
+            Duration::from_secs(2 * 60 * 60)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        for duration in ["", "10", "m", "-5m", "1.5m", "10d", "5é"] {
+            assert!(parse_duration(duration).is_err(), "{}", duration);
+        }
+    }
+
+    #[test]
+    fn rejects_durations_that_overflow() {
+        assert!(parse_duration(&format!("{}h", u64::MAX)).is_err());
+        assert!(parse_duration(&format!("{}s", u64::MAX)).is_ok());
+    }
+
+    #[test]
+    fn sorts_warnings_longest_first_without_duplicates() {
+        assert_eq!(
+            parse_warnings(&warnings(&["10s", "1m", "10m", "60s"])).unwrap(),
+            vec![
+                Duration::from_secs(600),
+                Duration::from_secs(60),
+                Duration::from_secs(10),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_warnings_with_an_invalid_time() {
+        assert!(parse_warnings(&warnings(&["10m", "soon"])).is_err());
+    }
+
+    #[test]
+    fn default_warnings_parse() {
+        assert_eq!(parse_warnings(&default_warnings()).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn accepts_crontab_and_seconds_cron_expressions() {
+        assert!(parse_cron("0 4 * * *").is_ok());
+        assert!(parse_cron("30 0 4 * * *").is_ok());
+        assert!(parse_cron("every night").is_err());
+    }
+
+    #[test]
+    fn reads_cron_expressions_in_the_time_zone() {
+        let schedule = parse_cron("0 4 * * *").unwrap();
+        let time_zone = parse_time_zone("Europe/Berlin").unwrap();
+        let next_restart = next_in_time_zone(&schedule, time_zone).unwrap();
+
+        assert_eq!(
+            next_restart
+                .with_timezone(&time_zone)
+                .format("%H:%M")
+                .to_string(),
+            "04:00"
+        );
+        assert!(parse_time_zone("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn describes_durations() {
+        assert_eq!(describe_duration(Duration::from_secs(3600)), "1 hour");
+        assert_eq!(describe_duration(Duration::from_secs(600)), "10 minutes");
+        assert_eq!(describe_duration(Duration::from_secs(90)), "90 seconds");
+        assert_eq!(describe_duration(Duration::from_secs(1)), "1 second");
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CROSS_MARK;
+use emoji::symbols::warning::WARNING;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::server::control::stop::{StopIntent, StopReason};
+
+/// Becomes `true` once Stainless has been asked to shut down.
+pub type ShutdownReceiver = watch::Receiver<bool>;
+
+/// How long Stainless waits for a server to stop when Stainless shuts down. The server is first
+/// saved and stopped through its console, then terminated, and finally killed.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownTimeouts {
+    /// Seconds to wait for the server to stop after its stop command before terminating it.
+    pub stop_timeout_seconds: u64,
+    /// Seconds to wait for the server to exit after terminating it before killing it.
+    pub terminate_timeout_seconds: u64,
+}
+
+impl Default for ShutdownTimeouts {
+    fn default() -> Self {
+        ShutdownTimeouts {
+            stop_timeout_seconds: 60,
+            terminate_timeout_seconds: 10,
+        }
+    }
+}
+
+/// Listens for Ctrl-C and SIGTERM. The first signal records a stop request for every server and
+/// asks them all to shut down.
+pub fn listen_for_shutdown_signals(stop_intents: Vec<Arc<StopIntent>>) -> ShutdownReceiver {
+    let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = wait_for_shutdown_signal().await {
+                println!(
+                    "{} Unable to listen for shutdown signals: {}",
+                    CROSS_MARK.glyph, e
+                );
+                std::future::pending::<()>().await;
+            }
+
+            if *shutdown_sender.borrow() {
+                println!(
+                    "{} Already shutting down; waiting for servers to stop...",
+                    WARNING.glyph
+                );
+                continue;
+            }
+
+            println!(
+                "{} Shutdown requested; stopping all servers...",
+                INFORMATION.glyph
+            );
+
+            for stop_intent in &stop_intents {
+                stop_intent.record(StopReason::Signal);
+            }
+
+            let _ = shutdown_sender.send(true);
+        }
+    });
+
+    shutdown_receiver
+}
+
+#[cfg(unix)]
+async fn wait_for_shutdown_signal() -> crate::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result?,
+        _ = terminate.recv() => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn wait_for_shutdown_signal() -> crate::Result<()> {
+    Ok(tokio::signal::ctrl_c().await?)
+}
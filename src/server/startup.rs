@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::constants::STARTUP_TIMES_FILE_NAME;
+
+static READY_LINE_PREFIX: &str = "Done (";
+static READY_LINE_SUFFIX: &str = "s)!";
+
+/// How long one build of a server took to start.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub struct StartupTimes {
+    pub starts: u32,
+    pub last_seconds: f64,
+    pub average_seconds: f64,
+}
+
+/// Reads the startup time from the message a server logs once it has finished starting, such as
+/// `Done (12.345s)! For help, type "help"`, with the log line's prefix already stripped. Only a
+/// message that starts this way is the server's own, so chat that mentions it is ignored.
+pub fn parse_ready_line(message: &str) -> Option<Duration> {
+    let seconds = message.strip_prefix(READY_LINE_PREFIX)?;
+    let seconds_length = seconds.find(READY_LINE_SUFFIX)?;
+    let seconds: f64 = seconds[..seconds_length].parse().ok()?;
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Loads the startup times recorded in a server's working directory, keyed by build.
+pub fn load_startup_times(working_directory: &Path) -> BTreeMap<String, StartupTimes> {
+    fs::read_to_string(working_directory.join(STARTUP_TIMES_FILE_NAME))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Records how long a build took to start, returning its updated startup times.
+pub fn record_startup_time(
+    working_directory: &Path,
+    build_name: &str,
+    startup: Duration,
+) -> crate::Result<StartupTimes> {
+    let mut startup_times = load_startup_times(working_directory);
+    let build_times = startup_times.entry(build_name.to_string()).or_default();
+
+    let seconds = startup.as_secs_f64();
+    build_times.average_seconds = (build_times.average_seconds * build_times.starts as f64
+        + seconds)
+        / (build_times.starts + 1) as f64;
+    build_times.starts += 1;
+    build_times.last_seconds = seconds;
+    let build_times = *build_times;
+
+    fs::write(
+        working_directory.join(STARTUP_TIMES_FILE_NAME),
+        serde_json::to_string_pretty(&startup_times)?,
+    )?;
+
+    Ok(build_times)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ready_line() {
+        assert_eq!(
+            parse_ready_line("Done (12.345s)! For help, type \"help\""),
+            Some(Duration::from_millis(12345))
+        );
+        assert_eq!(
+            parse_ready_line("Done (3.5s)!"),
+            Some(Duration::from_millis(3500))
+        );
+    }
+
+    #[test]
+    fn ready_line_must_start_the_message() {
+        assert_eq!(parse_ready_line("Steve said Done (1.0s)!"), None);
+        assert_eq!(parse_ready_line("Preparing level \"world\""), None);
+    }
+
+    #[test]
+    fn rejects_invalid_startup_times() {
+        assert_eq!(parse_ready_line("Done (-1.0s)!"), None);
+        assert_eq!(parse_ready_line("Done (NaNs)!"), None);
+        assert_eq!(parse_ready_line("Done (infs)!"), None);
+        assert_eq!(parse_ready_line("Done (1e300s)!"), None);
+        assert_eq!(parse_ready_line("Done (soons)!"), None);
+    }
+}
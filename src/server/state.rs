@@ -0,0 +1,316 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use emoji::symbols::other_symbol::CROSS_MARK;
+use emoji::symbols::warning::WARNING;
+use serde_json::json;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, watch};
+
+/// State transitions buffered for each subscriber before the slowest one starts missing them.
+pub static STATE_TRANSITION_CAPACITY: usize = 256;
+
+/// Where a server is in its lifecycle under the supervisor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ServerState {
+    /// Looking for a newer build of the server.
+    CheckingUpdates,
+    /// Downloading a newer build of the server.
+    Downloading,
+    /// The server process is starting.
+    Starting,
+    /// The server logged that it finished starting, taking `startup` to do so.
+    Running { startup: Duration },
+    /// The server was asked to stop and is shutting down.
+    Stopping,
+    /// The server exited with a failure, or could not be stopped cleanly.
+    Crashed,
+    /// Waiting `delay` before restarting the server after it failed.
+    BackingOff { delay: Duration },
+    /// The server is not running.
+    Stopped,
+}
+
+impl ServerState {
+    /// Name of the state in machine-readable output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServerState::CheckingUpdates => "checking-updates",
+            ServerState::Downloading => "downloading",
+            ServerState::Starting => "starting",
+            ServerState::Running { .. } => "running",
+            ServerState::Stopping => "stopping",
+            ServerState::Crashed => "crashed",
+            ServerState::BackingOff { .. } => "backing-off",
+            ServerState::Stopped => "stopped",
+        }
+    }
+
+    /// Whether the supervisor is expected to move a server from this state to `next`.
+    pub fn can_become(&self, next: &ServerState) -> bool {
+        use ServerState::*;
+
+        matches!(
+            (self, next),
+            (Stopped | Crashed | BackingOff { .. }, CheckingUpdates)
+                | (CheckingUpdates, Downloading | Starting)
+                | (Downloading, Starting)
+                | (CheckingUpdates | Downloading, Crashed)
+                | (Starting, Running { .. })
+                | (Starting | Running { .. }, Stopping)
+                | (Starting | Running { .. } | Stopping, Crashed)
+                | (Stopped | Crashed, BackingOff { .. })
+                | (_, Stopped)
+        )
+    }
+}
+
+impl Display for ServerState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerState::CheckingUpdates => write!(f, "checking for updates"),
+            ServerState::Downloading => write!(f, "downloading"),
+            ServerState::Starting => write!(f, "starting"),
+            ServerState::Running { .. } => write!(f, "running"),
+            ServerState::Stopping => write!(f, "stopping"),
+            ServerState::Crashed => write!(f, "crashed"),
+            ServerState::BackingOff { delay } => {
+                write!(f, "backing off for {} second(s)", delay.as_secs())
+            }
+            ServerState::Stopped => write!(f, "stopped"),
+        }
+    }
+}
+
+/// A change in the state of one server, and when it happened.
+#[derive(Clone, Debug)]
+pub struct StateTransition {
+    pub server_name: String,
+    pub from: ServerState,
+    pub to: ServerState,
+    pub at: DateTime<Utc>,
+}
+
+pub type ServerStateReceiver = watch::Receiver<ServerState>;
+pub type StateTransitionSender = broadcast::Sender<StateTransition>;
+pub type StateTransitionReceiver = broadcast::Receiver<StateTransition>;
+
+/// The state of one server, published both as its current value and as a stream of transitions
+/// shared by all servers.
+pub struct ServerLifecycle {
+    server_name: String,
+    state_sender: watch::Sender<ServerState>,
+    state_receiver: ServerStateReceiver,
+    transitions: StateTransitionSender,
+}
+
+impl ServerLifecycle {
+    pub fn new(server_name: String, transitions: StateTransitionSender) -> ServerLifecycle {
+        let (state_sender, state_receiver) = watch::channel(ServerState::Stopped);
+
+        ServerLifecycle {
+            server_name,
+            state_sender,
+            state_receiver,
+            transitions,
+        }
+    }
+
+    pub fn state(&self) -> ServerState {
+        *self.state_receiver.borrow()
+    }
+
+    pub fn subscribe(&self) -> ServerStateReceiver {
+        self.state_receiver.clone()
+    }
+
+    /// Moves the server to a new state, publishing the transition. Unexpected transitions are
+    /// still made, but reported.
+    pub fn transition(&self, next: ServerState) {
+        let previous = self.state();
+        if previous == next {
+            return;
+        }
+
+        if !previous.can_become(&next) {
+            println!(
+                "{} Server {} unexpectedly went from {} to {}",
+                WARNING.glyph, self.server_name, previous, next
+            );
+        }
+
+        let _ = self.state_sender.send(next);
+        // Nobody may be listening, in which case the transition is simply dropped.
+        let _ = self.transitions.send(StateTransition {
+            server_name: self.server_name.clone(),
+            from: previous,
+            to: next,
+            at: Utc::now(),
+        });
+    }
+}
+
+impl StateTransition {
+    /// The transition as a single line of JSON.
+    pub fn to_json_line(&self) -> String {
+        let mut transition = json!({
+            "at": self.at.to_rfc3339(),
+            "server": self.server_name,
+            "from": self.from.name(),
+            "to": self.to.name(),
+        });
+        match self.to {
+            ServerState::Running { startup } => {
+                transition["startup_seconds"] = json!(startup.as_secs_f64())
+            }
+            ServerState::BackingOff { delay } => {
+                transition["delay_seconds"] = json!(delay.as_secs())
+            }
+            _ => {}
+        }
+
+        transition.to_string()
+    }
+}
+
+/// Appends every state transition to a file as JSON lines, for tools that follow the supervisor.
+pub async fn write_state_transitions(path: PathBuf, mut transitions: StateTransitionReceiver) {
+    let mut events_file = match OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+    {
+        Ok(events_file) => events_file,
+        Err(e) => {
+            println!(
+                "{} Unable to open events file {}: {}",
+                CROSS_MARK.glyph,
+                path.display(),
+                e
+            );
+            return;
+        }
+    };
+
+    loop {
+        let transition = match transitions.recv().await {
+            Ok(transition) => transition,
+            Err(RecvError::Lagged(missed)) => {
+                println!(
+                    "{} {} state transition(s) were not written to {}",
+                    WARNING.glyph,
+                    missed,
+                    path.display()
+                );
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        let line = format!("{}\n", transition.to_json_line());
+        let write_result = match events_file.write_all(line.as_bytes()).await {
+            Ok(_) => events_file.flush().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = write_result {
+            println!(
+                "{} Unable to write to events file {}: {}",
+                CROSS_MARK.glyph,
+                path.display(),
+                e
+            );
+            return;
+        }
+    }
+}
+
+/// Waits until the server is running, returning how long it took to start. Returns `None` when the
+/// server stops first or the state is no longer published.
+pub async fn wait_until_ready(state: &mut ServerStateReceiver) -> Option<Duration> {
+    loop {
+        match *state.borrow_and_update() {
+            ServerState::Running { startup } => return Some(startup),
+            ServerState::Stopping | ServerState::Crashed | ServerState::Stopped => return None,
+            _ => {}
+        }
+
+        if state.changed().await.is_err() {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_a_server_through_a_clean_run() {
+        let states = [
+            ServerState::Stopped,
+            ServerState::CheckingUpdates,
+            ServerState::Downloading,
+            ServerState::Starting,
+            ServerState::Running {
+                startup: Duration::from_secs(5),
+            },
+            ServerState::Stopping,
+            ServerState::Stopped,
+        ];
+
+        for pair in states.windows(2) {
+            assert!(pair[0].can_become(&pair[1]), "{} -> {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn follows_a_crashing_server_through_its_backoff() {
+        let states = [
+            ServerState::Starting,
+            ServerState::Crashed,
+            ServerState::BackingOff {
+                delay: Duration::from_secs(10),
+            },
+            ServerState::CheckingUpdates,
+            ServerState::Starting,
+        ];
+
+        for pair in states.windows(2) {
+            assert!(pair[0].can_become(&pair[1]), "{} -> {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn any_state_can_stop() {
+        for state in [
+            ServerState::CheckingUpdates,
+            ServerState::Starting,
+            ServerState::Crashed,
+            ServerState::BackingOff {
+                delay: Duration::from_secs(1),
+            },
+        ] {
+            assert!(state.can_become(&ServerState::Stopped), "{}", state);
+        }
+    }
+
+    #[test]
+    fn rejects_skipped_states() {
+        let running = ServerState::Running {
+            startup: Duration::from_secs(5),
+        };
+
+        assert!(!ServerState::Stopped.can_become(&ServerState::Starting));
+        assert!(!ServerState::Stopped.can_become(&running));
+        assert!(!ServerState::Stopping.can_become(&running));
+        assert!(!running.can_become(&ServerState::CheckingUpdates));
+        assert!(!ServerState::Starting.can_become(&ServerState::BackingOff {
+            delay: Duration::from_secs(1),
+        }));
+    }
+}
@@ -0,0 +1,343 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use emoji::symbols::other_symbol::{CHECK_MARK, CROSS_MARK};
+use emoji::symbols::warning::WARNING;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::Sender;
+use tokio::time::Instant;
+
+use crate::config::constants::{SERVER_INFO_FILE_NAME, STAGED_SERVER_DIR_NAME};
+use crate::config::ServerType;
+use crate::server::control::stop::{StopIntent, StopReason};
+use crate::server::hooks::{run_hook_reporting_failure, Hook, HookContext};
+use crate::server::schedule::{default_warnings, parse_warnings, restart_with_warnings};
+use crate::server::state::{wait_until_ready, ServerStateReceiver};
+use crate::server::{Server, ServerApplication};
+
+/// Checks for newer builds while the server runs, downloading them next to the installed one so
+/// that the next start swaps them in instead of waiting on the download.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackgroundUpdates {
+    /// Minutes between checks for a newer build, the first one being made this long after the
+    /// server starts.
+    pub check_interval_minutes: u64,
+    /// Restarts the server once a newer build is staged instead of waiting for its next restart.
+    pub restart_when_staged: bool,
+    /// How long before such a restart players are warned, such as `5m` or `10s`.
+    pub restart_warnings: Vec<String>,
+}
+
+impl Default for BackgroundUpdates {
+    fn default() -> Self {
+        BackgroundUpdates {
+            check_interval_minutes: 60,
+            restart_when_staged: false,
+            restart_warnings: default_warnings(),
+        }
+    }
+}
+
+/// Directory in a server's working directory that newer builds are staged in.
+pub fn staged_server_directory(working_directory: &Path) -> PathBuf {
+    working_directory.join(STAGED_SERVER_DIR_NAME)
+}
+
+/// Checks for a newer build of a server every so often while it runs and stages it for the next
+/// start, restarting the server once one is staged when configured to. Checks begin once the server
+/// is running, so that a restart never reaches the console of the next process.
+pub async fn run_background_updates(
+    server_type: ServerType,
+    http_client: Client,
+    input_sender: Sender<String>,
+    stop_intent: Arc<StopIntent>,
+    state: ServerStateReceiver,
+) {
+    match &server_type {
+        ServerType::PaperMC(server) => {
+            stage_updates(
+                server,
+                &server_type,
+                &http_client,
+                &input_sender,
+                &stop_intent,
+                state,
+            )
+            .await
+        }
+        ServerType::Vanilla(server) => {
+            stage_updates(
+                server,
+                &server_type,
+                &http_client,
+                &input_sender,
+                &stop_intent,
+                state,
+            )
+            .await
+        }
+        ServerType::Fabric(server) => {
+            stage_updates(
+                server,
+                &server_type,
+                &http_client,
+                &input_sender,
+                &stop_intent,
+                state,
+            )
+            .await
+        }
+        ServerType::Forge(server) => {
+            stage_updates(
+                server,
+                &server_type,
+                &http_client,
+                &input_sender,
+                &stop_intent,
+                state,
+            )
+            .await
+        }
+        ServerType::Fork(server) => {
+            stage_updates(
+                server,
+                &server_type,
+                &http_client,
+                &input_sender,
+                &stop_intent,
+                state,
+            )
+            .await
+        }
+        ServerType::Custom(server) => {
+            stage_updates(
+                server,
+                &server_type,
+                &http_client,
+                &input_sender,
+                &stop_intent,
+                state,
+            )
+            .await
+        }
+    }
+}
+
+async fn stage_updates<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
+    server_type: &ServerType,
+    http_client: &Client,
+    input_sender: &Sender<String>,
+    stop_intent: &StopIntent,
+    mut state: ServerStateReceiver,
+) {
+    let background_updates = match server.background_updates() {
+        Some(background_updates) => background_updates,
+        None => return,
+    };
+    let restart_warnings = if background_updates.restart_when_staged {
+        match parse_warnings(&background_updates.restart_warnings) {
+            Ok(restart_warnings) => Some(restart_warnings),
+            Err(e) => {
+                println!(
+                    "{} {} will not restart for staged updates: {}",
+                    WARNING.glyph,
+                    server.server_name(),
+                    e
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if wait_until_ready(&mut state).await.is_none() {
+        return;
+    }
+
+    let check_interval = Duration::from_secs(background_updates.check_interval_minutes.max(1) * 60);
+    let mut checks = tokio::time::interval_at(Instant::now() + check_interval, check_interval);
+
+    loop {
+        checks.tick().await;
+
+        match stage_update(server, http_client).await {
+            Ok(Some(staged_server_app)) => {
+                println!(
+                    "{} Staged {} for {}; it is swapped in at the next start",
+                    CHECK_MARK.glyph,
+                    staged_server_app.build_name(),
+                    server.server_name()
+                );
+
+                if let Some(restart_warnings) = &restart_warnings {
+                    let first_warning = restart_warnings.first().copied().unwrap_or_default();
+                    let restart_time = Utc::now()
+                        + chrono::Duration::from_std(first_warning)
+                            .unwrap_or_else(|_| chrono::Duration::zero());
+
+                    restart_with_warnings(
+                        server.server_name(),
+                        server_type,
+                        input_sender,
+                        stop_intent,
+                        restart_time,
+                        restart_warnings,
+                        StopReason::UpdateStaged,
+                    )
+                    .await;
+                    return;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => println!(
+                "{} Background update check for {} failed: {}",
+                WARNING.glyph,
+                server.server_name(),
+                e
+            ),
+        }
+    }
+}
+
+/// Downloads a build newer than the staged one, or the installed one when none is staged, into the
+/// staging directory. Returns the newly staged build, if there is one.
+async fn stage_update<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
+    http_client: &Client,
+) -> crate::Result<Option<A>> {
+    let current_server_app = match server.load_staged_server_app().ok() {
+        Some(server_app) => server_app,
+        None => server.load_saved_server_app()?,
+    };
+
+    let updated_server_app = match current_server_app
+        .check_for_updated_server(server, http_client)
+        .await?
+    {
+        Some(updated_server_app) => updated_server_app,
+        None => return Ok(None),
+    };
+
+    // A build staged earlier is replaced as a whole, since some backends stage several files.
+    let staged_directory = staged_server_directory(server.working_directory());
+    if staged_directory.exists() {
+        fs::remove_dir_all(&staged_directory)?;
+    }
+    fs::create_dir_all(&staged_directory)?;
+    updated_server_app
+        .download_server(server, &staged_directory, http_client)
+        .await?;
+    updated_server_app.stage_server_info(server)?;
+
+    Ok(Some(updated_server_app))
+}
+
+/// Swaps a staged build in for the installed one, returning it. Returns `None` when no build is
+/// staged or it could not be swapped in, in which case the installed build is left in place.
+pub async fn promote_staged_server_app<S: Server<S, A>, A: ServerApplication<S, A>>(
+    server: &S,
+) -> Option<A> {
+    let staged_server_app = server.load_staged_server_app().ok()?;
+    let staged_directory = staged_server_directory(server.working_directory());
+    if !staged_server_app.matches_config(server) {
+        println!(
+            "{} Discarding staged server {}, which no longer matches the configuration of {}",
+            WARNING.glyph,
+            staged_server_app.application_name(),
+            server.server_name()
+        );
+        if let Err(e) = fs::remove_dir_all(&staged_directory) {
+            println!(
+                "{} Unable to remove staging directory {}: {}",
+                WARNING.glyph,
+                staged_directory.display(),
+                e
+            );
+        }
+        return None;
+    }
+    let existing_server_app = server.load_saved_server_app().ok();
+
+    let mut hook_context =
+        HookContext::new(server).with("STAINLESS_NEW_BUILD", staged_server_app.build_name());
+    if let Some(server_app) = &existing_server_app {
+        hook_context = hook_context.with("STAINLESS_OLD_BUILD", server_app.build_name());
+    }
+    run_hook_reporting_failure(server.hooks(), Hook::PreUpdate, &hook_context).await;
+
+    if let Err(e) = move_directory_contents(
+        &staged_directory,
+        server.working_directory(),
+        &[SERVER_INFO_FILE_NAME],
+    ) {
+        println!(
+            "{} Unable to swap in staged server {}: {}",
+            CROSS_MARK.glyph,
+            staged_server_app.application_name(),
+            e
+        );
+        return None;
+    }
+
+    if let Some(server_app) = existing_server_app {
+        if server_app.application_name() != staged_server_app.application_name() {
+            if let Err(e) = server_app.delete_server(server, Some(&staged_server_app)) {
+                println!(
+                    "{} Failed to delete old server app: {}",
+                    CROSS_MARK.glyph, e
+                );
+            }
+        }
+    }
+    if let Err(e) = staged_server_app.save_server_info(server) {
+        println!("{} Unable to save server info: {}", CROSS_MARK.glyph, e);
+    }
+    if let Err(e) = fs::remove_dir_all(&staged_directory) {
+        println!(
+            "{} Unable to remove staging directory {}: {}",
+            WARNING.glyph,
+            staged_directory.display(),
+            e
+        );
+    }
+
+    println!(
+        "{} Swapped in staged server {}!",
+        CHECK_MARK.glyph,
+        staged_server_app.application_name()
+    );
+    run_hook_reporting_failure(server.hooks(), Hook::PostUpdate, &hook_context).await;
+
+    Some(staged_server_app)
+}
+
+/// Moves the contents of one directory into another, merging directories into existing ones and
+/// replacing existing files. Entries named in `skipped_names` stay where they are.
+pub fn move_directory_contents(
+    source: &Path,
+    destination: &Path,
+    skipped_names: &[&str],
+) -> crate::Result<()> {
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        if skipped_names.iter().any(|name| entry.file_name() == *name) {
+            continue;
+        }
+
+        let entry_destination = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() && entry_destination.is_dir() {
+            move_directory_contents(&entry.path(), &entry_destination, &[])?;
+        } else {
+            fs::rename(entry.path(), entry_destination)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use std::fmt::{Display, Formatter};
+use std::fs::remove_file;
+use std::path::Path;
+use std::process::ExitStatus;
+
+use async_trait::async_trait;
+use emoji::symbols::alphanum::INFORMATION;
+use emoji::symbols::other_symbol::CHECK_MARK;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::server::process::{run_server_process, ProcessChannels};
+use crate::server::{Server, ServerApplication, ServerSettings};
+
+pub mod query;
+
+/// Version that follows the newest release.
+pub const LATEST_RELEASE: &str = "latest.release";
+/// Version that follows the newest snapshot, or the newest release when it is newer.
+pub const LATEST_SNAPSHOT: &str = "latest.snapshot";
+
+/// A server running the server jar Mojang publishes for a Minecraft version.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct VanillaServer {
+    /// Minecraft version to run, such as `1.18.2`, or `latest.release` or `latest.snapshot` to
+    /// follow the newest version.
+    pub version: String,
+    #[serde(flatten)]
+    pub settings: ServerSettings,
+}
+
+impl Server<VanillaServer, VanillaServerApp> for VanillaServer {
+    fn settings(&self) -> &ServerSettings {
+        &self.settings
+    }
+
+    fn default_version_check_client(&self) -> VanillaServerApp {
+        VanillaServerApp::default()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VanillaServerApp {
+    /// Version the server application runs, such as `1.18.2`.
+    pub version: String,
+    pub server_download: Download,
+}
+
+impl Display for VanillaServerApp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{Version: {}, Download: {}}}",
+            self.version, self.server_download
+        )
+    }
+}
+
+#[async_trait]
+impl ServerApplication<VanillaServer, VanillaServerApp> for VanillaServerApp {
+    fn application_name(&self) -> &str {
+        &self.server_download.name
+    }
+
+    fn build_name(&self) -> String {
+        format!("vanilla-{}", self.version)
+    }
+
+    fn matches_config(&self, config: &VanillaServer) -> bool {
+        config.version == LATEST_RELEASE
+            || config.version == LATEST_SNAPSHOT
+            || config.version == self.version
+    }
+
+    async fn check_for_updated_server(
+        &self,
+        config: &VanillaServer,
+        http_client: &Client,
+    ) -> crate::Result<Option<VanillaServerApp>> {
+        let latest_client = query::vanilla_server_for_version(&config.version, http_client).await?;
+
+        // The configured version may follow the newest release or have been changed, so any
+        // server other than the installed one is an update. Mojang occasionally republishes the
+        // jar of a version, which only shows in its hash.
+        if latest_client.version != self.version
+            || latest_client.server_download.sha1 != self.server_download.sha1
+        {
+            println!(
+                "{} Server version {} is available",
+                CHECK_MARK.glyph, latest_client.version
+            );
+            Ok(Some(latest_client))
+        } else {
+            println!("{} No newer server is available!", CHECK_MARK.glyph);
+            Ok(None)
+        }
+    }
+
+    async fn download_server(
+        &self,
+        _config: &VanillaServer,
+        directory: &Path,
+        http_client: &Client,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Downloading {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        query::download_server_application(
+            self,
+            &directory.join(self.application_name()),
+            http_client,
+        )
+        .await
+    }
+
+    fn delete_server(
+        &self,
+        config: &VanillaServer,
+        _replacement: Option<&VanillaServerApp>,
+    ) -> crate::Result<()> {
+        println!(
+            "{} Removing {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        remove_file(config.working_directory().join(self.application_name()))?;
+
+        Ok(())
+    }
+
+    async fn start_server(
+        &self,
+        server_config: &VanillaServer,
+        channels: ProcessChannels<'_>,
+    ) -> crate::Result<ExitStatus> {
+        println!(
+            "{} Starting {}...",
+            INFORMATION.glyph,
+            self.application_name()
+        );
+
+        let mut command = Command::new("java");
+        command
+            .args(server_config.jvm_arguments())
+            .arg("-jar")
+            .arg(self.application_name())
+            .arg("nogui")
+            .current_dir(server_config.working_directory());
+
+        run_server_process(self, server_config, command, channels).await
+    }
+}
+
+impl Default for VanillaServerApp {
+    fn default() -> Self {
+        VanillaServerApp {
+            version: String::from(""),
+            server_download: Download {
+                name: String::from(""),
+                url: String::from(""),
+                sha1: vec![],
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Download {
+    pub name: String,
+    pub url: String,
+    pub sha1: Vec<u8>,
+}
+
+impl Display for Download {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{{Name: {}, SHA1: {}}}",
+            self.name,
+            hex::encode(&self.sha1)
+        )
+    }
+}
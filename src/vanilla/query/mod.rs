@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use anyhow::Error;
+use reqwest::Client;
+use sha1::Sha1;
+
+use crate::server::download::download_verified_file;
+use crate::vanilla::query::response_schema::{VersionManifestResponse, VersionResponse};
+use crate::vanilla::{Download, VanillaServerApp};
+
+mod response_schema;
+
+static VERSION_MANIFEST_URL: &str = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
+
+pub async fn vanilla_versions(http_client: &Client) -> crate::Result<Vec<String>> {
+    Ok(call_version_manifest_api(http_client)
+        .await?
+        .versions
+        .into_iter()
+        .map(|version| version.id)
+        .collect())
+}
+
+/// Looks up the server of a version id, `latest.release` or `latest.snapshot`.
+pub async fn vanilla_server_for_version(
+    version: &str,
+    http_client: &Client,
+) -> crate::Result<VanillaServerApp> {
+    let version_manifest = call_version_manifest_api(http_client).await?;
+    let manifest_version = match version_manifest.find_version(version) {
+        Some(manifest_version) => manifest_version,
+        None => {
+            return Err(Error::msg(format!(
+                "Mojang's version manifest has no version {}",
+                version
+            )))
+        }
+    };
+
+    let version_response = call_version_api(&manifest_version.url, http_client).await?;
+    let server_download = match version_response.server_download() {
+        Some(server_download) => server_download,
+        None => {
+            return Err(Error::msg(format!(
+                "no server download exists for version {}",
+                version_response.id
+            )))
+        }
+    };
+
+    Ok(VanillaServerApp {
+        server_download: Download {
+            name: format!("minecraft_server.{}.jar", version_response.id),
+            url: server_download.url.clone(),
+            sha1: hex::decode(&server_download.sha1)?,
+        },
+        version: version_response.id,
+    })
+}
+
+pub async fn download_server_application(
+    server_app: &VanillaServerApp,
+    client_file_path: &Path,
+    http_client: &Client,
+) -> crate::Result<()> {
+    download_verified_file::<Sha1>(
+        &server_app.server_download.url,
+        client_file_path,
+        &server_app.server_download.sha1,
+        http_client,
+    )
+    .await
+}
+
+async fn call_version_manifest_api(http_client: &Client) -> crate::Result<VersionManifestResponse> {
+    Ok(http_client
+        .get(VERSION_MANIFEST_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<VersionManifestResponse>()
+        .await?)
+}
+
+async fn call_version_api(url: &str, http_client: &Client) -> crate::Result<VersionResponse> {
+    Ok(http_client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<VersionResponse>()
+        .await?)
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+static VANILLA_SERVER_DOWNLOAD_NAME: &str = "server";
+
+#[derive(Deserialize)]
+pub struct VersionManifestResponse {
+    pub latest: LatestVersions,
+    pub versions: Vec<ManifestVersion>,
+}
+
+impl VersionManifestResponse {
+    /// Finds the version named by `latest.release`, `latest.snapshot` or a version id.
+    pub fn find_version(&self, version: &str) -> Option<&ManifestVersion> {
+        let version_id = match version {
+            crate::vanilla::LATEST_RELEASE => self.latest.release.as_str(),
+            crate::vanilla::LATEST_SNAPSHOT => self.latest.snapshot.as_str(),
+            version_id => version_id,
+        };
+
+        self.versions
+            .iter()
+            .find(|version| version.id == version_id)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LatestVersions {
+    pub release: String,
+    pub snapshot: String,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct ManifestVersion {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub version_type: String,
+    pub url: String,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+pub struct VersionResponse {
+    pub id: String,
+    pub downloads: HashMap<String, Download>,
+}
+
+impl VersionResponse {
+    pub fn server_download(&self) -> Option<&Download> {
+        self.downloads.get(VANILLA_SERVER_DOWNLOAD_NAME)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Clone)]
+pub struct Download {
+    pub sha1: String,
+    pub size: u64,
+    pub url: String,
+}